@@ -0,0 +1,100 @@
+use std::{fmt, pin::Pin, time::Duration};
+use btleplug::api::CentralEvent;
+use btleplug::platform::PeripheralId;
+use futures::stream::{Stream, StreamExt};
+
+pub type CentralEventStream = Pin<Box<dyn Stream<Item = CentralEvent> + Send>>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConnectionState {
+    Scanning,
+    Connecting,
+    // Waiting for the user to accept the pairing dialog on the watch
+    Pairing,
+    Syncing,
+    Connected,
+    // Waiting before the next connection attempt
+    Backoff(Duration)
+}
+
+impl ConnectionState {
+    // Whether the watch can be talked to (the initial sync happens while already paired)
+    pub fn is_usable(self) -> bool {
+        matches!(self, ConnectionState::Syncing | ConnectionState::Connected)
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionState::Scanning => write!(f, "scanning..."),
+            ConnectionState::Connecting => write!(f, "connecting..."),
+            ConnectionState::Pairing => write!(f, "pairing (accept it on the watch)..."),
+            ConnectionState::Syncing => write!(f, "syncing..."),
+            ConnectionState::Connected => write!(f, "connected"),
+            ConnectionState::Backoff(delay) => write!(f, "retrying in {}s...", delay.as_secs())
+        }
+    }
+}
+
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// Exponential delay between failed connection attempts, starting over once a connection fully succeeds
+pub struct Backoff {
+    next_delay: Duration
+}
+
+impl Backoff {
+    pub const fn new() -> Self {
+        Self {
+            next_delay: INITIAL_BACKOFF
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next_delay;
+        self.next_delay = (delay * 2).min(MAX_BACKOFF);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.next_delay = INITIAL_BACKOFF;
+    }
+}
+
+// Finishes when the adapter reports the peripheral as disconnected (or stops reporting events at all)
+pub async fn wait_disconnected(events: &mut CentralEventStream, id: &PeripheralId) {
+    while let Some(event) = events.next().await {
+        if let CentralEvent::DeviceDisconnected(event_id) = event {
+            if event_id == *id {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_max() {
+        let mut backoff = Backoff::new();
+        let delays: Vec<u64> = (0..9).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60, 60]);
+    }
+
+    #[test]
+    fn backoff_reset() {
+        let mut backoff = Backoff::new();
+        for _ in 0..4 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), Duration::from_secs(16));
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), INITIAL_BACKOFF);
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+    }
+}
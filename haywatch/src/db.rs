@@ -1,6 +1,7 @@
 use binrw::{BinRead, BinWrite, BinReaderExt, BinWriterExt};
-use ls02::common::Date;
-use std::{env, path::PathBuf, fs::{File, OpenOptions}};
+use ls02::common::{Date, DistanceUnit, TimeFormat, UserGender, UserLiftWristMode};
+use ls02::general::user_info::UserInfo;
+use std::{env, path::{Path, PathBuf}, fs::{File, OpenOptions}};
 
 #[derive(BinRead, BinWrite, Clone, Debug)]
 #[brw(magic = b"HRDE")]
@@ -65,9 +66,9 @@ impl HeartRateDailyDatabase {
             let entry_name = entry.file_name();
             let w: Vec<&str> = entry_name.to_str().unwrap().split("-").collect();
             if w.len() == 3 {
-                let year = u16::from_str_radix(w[0], 10).unwrap();
-                let month = u8::from_str_radix(w[1], 10).unwrap();
-                let day = u8::from_str_radix(w[2], 10).unwrap();
+                let year = w[0].parse::<u16>().unwrap();
+                let month = w[1].parse::<u8>().unwrap();
+                let day = w[2].parse::<u8>().unwrap();
                 dates.push(Date::new(year, month, day))
             }
         }
@@ -83,7 +84,7 @@ impl HeartRateDailyDatabase {
     pub fn save(&self, date: Date) {
         let path = Self::get_db_path(date);
         // println!("Saving heart rate db: {:?}", path);
-        let mut file = OpenOptions::new().create(true).write(true).open(path).unwrap();
+        let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(path).unwrap();
         file.write_le(self).unwrap();
     }
 }
@@ -147,9 +148,9 @@ impl RecordedStepsDailyDatabase {
             let entry_name = entry.file_name();
             let w: Vec<&str> = entry_name.to_str().unwrap().split("-").collect();
             if w.len() == 3 {
-                let year = u16::from_str_radix(w[0], 10).unwrap();
-                let month = u8::from_str_radix(w[1], 10).unwrap();
-                let day = u8::from_str_radix(w[2], 10).unwrap();
+                let year = w[0].parse::<u16>().unwrap();
+                let month = w[1].parse::<u8>().unwrap();
+                let day = w[2].parse::<u8>().unwrap();
                 dates.push(Date::new(year, month, day))
             }
         }
@@ -165,7 +166,139 @@ impl RecordedStepsDailyDatabase {
     pub fn save(&self, date: Date) {
         let path = Self::get_db_path(date);
         println!("Saving recorded steps db: {:?}", path);
-        let mut file = OpenOptions::new().create(true).write(true).open(path).unwrap();
+        let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(path).unwrap();
         file.write_le(self).unwrap();
     }
-}
\ No newline at end of file
+}
+
+// Each watch gets its own (random) pair key, kept along with the rest of the databases
+pub fn pair_key_store() -> ls02::FileKeyStore {
+    ls02::FileKeyStore::new(env::current_exe().unwrap().parent().unwrap().join("db").join("pair_keys"))
+}
+
+// User info and display formats, pushed to the watch on every connection
+#[derive(BinRead, BinWrite, Clone, PartialEq, Eq, Debug)]
+#[brw(magic = b"PRFL")]
+pub struct WatchProfile {
+    pub height_cm: u8,
+    pub weight_kg: u8,
+    pub age: u8,
+    pub gender: UserGender,
+    pub step_goal: u16,
+    pub screen_show_timeout_seconds: u8,
+    pub lift_wrist_mode: UserLiftWristMode,
+    pub distance_unit: DistanceUnit,
+    pub time_fmt: TimeFormat
+}
+
+impl WatchProfile {
+    pub fn new() -> Self {
+        Self::from_user_info(&UserInfo::new(170, 70, 30, UserGender::Male), DistanceUnit::Metric, TimeFormat::H24)
+    }
+
+    pub fn from_user_info(user_info: &UserInfo, distance_unit: DistanceUnit, time_fmt: TimeFormat) -> Self {
+        Self {
+            height_cm: user_info.height_cm,
+            weight_kg: user_info.weight_kg,
+            age: user_info.age,
+            gender: user_info.gender,
+            step_goal: user_info.step_goal,
+            screen_show_timeout_seconds: user_info.screen_show_timeout_seconds,
+            lift_wrist_mode: user_info.lift_wrist_mode,
+            distance_unit,
+            time_fmt
+        }
+    }
+
+    pub fn user_info(&self) -> UserInfo {
+        UserInfo::new(self.height_cm, self.weight_kg, self.age, self.gender)
+            .with_step_goal(self.step_goal)
+            .with_screen_show_timeout_seconds(self.screen_show_timeout_seconds)
+            .with_lift_wrist_mode(self.lift_wrist_mode)
+    }
+
+    fn get_path() -> PathBuf {
+        ensure_parent_path(env::current_exe().unwrap().parent().unwrap().join("db").join("profile"))
+    }
+
+    // Missing, unreadable or out-of-range profiles fall back to the defaults
+    fn load_from(path: &Path) -> Self {
+        let profile: Option<Self> = File::open(path).ok().and_then(|mut file| file.read_le().ok());
+        match profile {
+            Some(profile) if profile.user_info().validate().is_ok() => profile,
+            _ => Self::new()
+        }
+    }
+
+    fn save_to(&self, path: &Path) {
+        let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(path).unwrap();
+        file.write_le(self).unwrap();
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::get_path())
+    }
+
+    pub fn save(&self) {
+        let path = Self::get_path();
+        println!("Saving profile: {:?}", path);
+        self.save_to(&path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test uses its own file, since tests run in parallel
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("haywatch-{}-{}", std::process::id(), name))
+    }
+
+    fn custom_profile() -> WatchProfile {
+        let user_info = UserInfo::new(182, 79, 41, UserGender::Female)
+            .with_step_goal(12000)
+            .with_screen_show_timeout_seconds(8)
+            .with_lift_wrist_mode(UserLiftWristMode::Off);
+        WatchProfile::from_user_info(&user_info, DistanceUnit::Imperial, TimeFormat::H12)
+    }
+
+    #[test]
+    fn profile_round_trip() {
+        let path = temp_path("profile-round-trip");
+        let profile = custom_profile();
+        profile.save_to(&path);
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PRFL");
+        assert_eq!(WatchProfile::load_from(&path), profile);
+        assert_eq!(WatchProfile::load_from(&path).user_info(), profile.user_info());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_profiles_fall_back() {
+        let path = temp_path("profile-invalid");
+        custom_profile().save_to(&path);
+
+        // Wrong magic
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[..4].copy_from_slice(b"HRDB");
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(WatchProfile::load_from(&path), WatchProfile::new());
+
+        // Truncated
+        std::fs::write(&path, b"PRFL\xB6").unwrap();
+        assert_eq!(WatchProfile::load_from(&path), WatchProfile::new());
+
+        // Out of the watch's range
+        let mut profile = custom_profile();
+        profile.height_cm = 0;
+        profile.save_to(&path);
+        assert_eq!(WatchProfile::load_from(&path), WatchProfile::new());
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(WatchProfile::load_from(&path), WatchProfile::new());
+    }
+}
@@ -1,36 +1,51 @@
-use eframe::{egui::{self, Context}, epaint::Stroke};
+use eframe::egui::{self, Context};
 use egui_plot::{BarChart, Bar, Plot, Text, PlotPoint, GridMark};
 use std::{sync::{Arc, Mutex}, ops::RangeInclusive};
 use std::error::Error;
 use std::time::Duration;
-use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter, WriteType, Characteristic, ValueNotification};
-use btleplug::platform::{Manager, Peripheral};
-use futures::stream::StreamExt;
+use btleplug::api::{BDAddr, Central as _, Peripheral as _};
+use btleplug::platform::{Adapter, Peripheral};
+use futures::{future, stream::StreamExt};
 use tokio::time;
-use ls02::{self, common::{Date, AlertType, INVALID_HEART_RATE}};
+use ls02::{self, common::{Date, AlertType, DistanceUnit, TimeFormat, UserGender, UserLiftWristMode, INVALID_HEART_RATE}, general::user_info::UserInfo, general::alert::{AlertMessage, TruncationPolicy}, general::forecast::{DayWeather, Forecast}};
+
+mod connection;
+use connection::ConnectionState;
 
 mod db;
+mod weather;
 
 struct WatchContext {
-    peripheral: Option<Peripheral>,
-    is_connected: bool,
+    // Only set while connected
+    watch: Option<ls02::Watch<Peripheral>>,
+    state: ConnectionState,
     battery: Option<u8>,
     pair_key: Option<ls02::common::PairKey>,
     firmware: String,
     hr_db_dates: Vec<Date>,
-    rs_db_dates: Vec<Date>
+    hr_sync_status: String,
+    rs_db_dates: Vec<Date>,
+    rs_sync_status: String,
+    forecast: Option<Forecast>,
+    weather_status: String,
+    profile_status: String
 }
 
 impl WatchContext {
     pub fn new() -> Self {
         Self {
-            peripheral: None,
-            is_connected: false,
+            watch: None,
+            state: ConnectionState::Scanning,
             battery: None,
             pair_key: None,
             firmware: String::new(),
             hr_db_dates: Vec::new(),
-            rs_db_dates: Vec::new()
+            hr_sync_status: String::new(),
+            rs_db_dates: Vec::new(),
+            rs_sync_status: String::new(),
+            forecast: None,
+            weather_status: String::new(),
+            profile_status: String::new()
         }
     }
 }
@@ -38,43 +53,179 @@ impl WatchContext {
 struct MainApp {
     started: bool,
     ctx: Arc<Mutex<WatchContext>>,
+    title: String,
     text: String,
     text_type: AlertType,
+    text_transliterate: bool,
     cur_hr_db_date_idx: usize,
     cur_hr_db_date_chart_bars: Option<Vec<Bar>>,
     cur_hr_db_day_entry: Option<db::HeartRateDailyDatabaseEntry>,
     cur_rs_db_date_idx: usize,
+    profile: db::WatchProfile,
     show_msg_window: bool,
     show_hr_window: bool,
-    show_rs_window: bool
+    show_rs_window: bool,
+    show_settings_window: bool
+}
+
+fn store_hr_history(history: &ls02::HeartRateHistory) {
+    if let Some(today) = history.today {
+        let mut db = db::HeartRateDailyDatabase::create_load_by_date(today.date);
+
+        let entry = db::HeartRateDailyDatabaseEntry {
+            hour: today.hour,
+            min: today.min,
+            heart_rate: INVALID_HEART_RATE,
+            max_heart_rate: today.max_heart_rate,
+            min_heart_rate: today.min_heart_rate,
+            avg_heart_rate: today.avg_heart_rate
+        };
+        db.push(entry);
+
+        db.save(today.date);
+    }
+
+    for history_entry in history.entries.iter() {
+        let mut db = db::HeartRateDailyDatabase::create_load_by_date(history_entry.date);
+
+        for (hour, min, heart_rate) in history_entry.samples() {
+            let entry = db::HeartRateDailyDatabaseEntry {
+                hour,
+                min,
+                heart_rate,
+                max_heart_rate: INVALID_HEART_RATE,
+                min_heart_rate: INVALID_HEART_RATE,
+                avg_heart_rate: INVALID_HEART_RATE
+            };
+            db.push(entry);
+        }
+
+        db.save(history_entry.date);
+    }
+}
+
+fn store_rs_history(history: &ls02::StepHistory) {
+    for history_entry in history.entries.iter() {
+        let mut db = db::RecordedStepsDailyDatabase::create_load_by_date(history_entry.date);
+
+        let new_walk_step_count = history_entry.new_walk_step_count;
+        if new_walk_step_count > 0 {
+            let entry = db::RecordedStepsDailyDatabaseEntry {
+                hour: history_entry.hour,
+                min: history_entry.last_new_walk_step_min,
+                new_step_kind: db::RecordedStepKind::Walk,
+                new_step_count: new_walk_step_count
+            };
+
+            println!("Walked {} steps at {:?} at {:02}:{:02}", new_walk_step_count, history_entry.date, entry.hour, entry.min);
+            db.push(entry);
+        }
+
+        let new_run_step_count = history_entry.new_run_step_count;
+        if new_run_step_count > 0 {
+            let entry = db::RecordedStepsDailyDatabaseEntry {
+                hour: history_entry.hour,
+                min: history_entry.last_new_run_step_min,
+                new_step_kind: db::RecordedStepKind::Run,
+                new_step_count: new_run_step_count
+            };
+
+            println!("Ran {} steps at {:?} at {:02}:{:02}", new_run_step_count, history_entry.date, entry.hour, entry.min);
+            db.push(entry);
+        }
+
+        db.save(history_entry.date);
+    }
 }
 
-async fn request_hr_data(watch: &Peripheral) -> Result<(), Box<dyn Error>> {
-    ls02::write::<ls02::data2::req::HeartRateDataRequest>(watch, ls02::data2::req::HeartRateDataRequest::new()).await?;
+async fn sync_hr_data(watch: &ls02::Watch<Peripheral>, watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context) -> ls02::Result<()> {
+    watch_ctx.lock().unwrap().hr_sync_status = "Retrieving heart rate data...".to_string();
+    let res = watch.sync_heart_rate(|entry_count| {
+        watch_ctx.lock().unwrap().hr_sync_status = format!("Retrieving heart rate data... ({} entries)", entry_count);
+        egui_ctx.request_repaint();
+    }).await;
+
+    let history = match res {
+        Ok(history) => history,
+        Err(err) => {
+            watch_ctx.lock().unwrap().hr_sync_status = format!("Unable to retrieve heart rate data: {}", err);
+            egui_ctx.request_repaint();
+            return Err(err);
+        }
+    };
+    store_hr_history(&history);
 
+    if let Ok(mut ctx) = watch_ctx.lock() {
+        ctx.hr_db_dates = db::HeartRateDailyDatabase::list_dates();
+        ctx.hr_sync_status = format!("Retrieved {} heart rate entries", history.entries.len());
+    }
+    egui_ctx.request_repaint();
     Ok(())
 }
 
-async fn request_rs_data(watch: &Peripheral) -> Result<(), Box<dyn Error>> {
-    ls02::write::<ls02::data2::req::RecordedStepsRequest>(watch, ls02::data2::req::RecordedStepsRequest::new()).await?;
+async fn sync_rs_data(watch: &ls02::Watch<Peripheral>, watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context) -> ls02::Result<()> {
+    watch_ctx.lock().unwrap().rs_sync_status = "Retrieving recorded step data...".to_string();
+    let res = watch.sync_recorded_steps(|entry_count| {
+        watch_ctx.lock().unwrap().rs_sync_status = format!("Retrieving recorded step data... ({} entries)", entry_count);
+        egui_ctx.request_repaint();
+    }).await;
+
+    let history = match res {
+        Ok(history) => history,
+        Err(err) => {
+            watch_ctx.lock().unwrap().rs_sync_status = format!("Unable to retrieve recorded step data: {}", err);
+            egui_ctx.request_repaint();
+            return Err(err);
+        }
+    };
+    store_rs_history(&history);
 
+    if let Ok(mut ctx) = watch_ctx.lock() {
+        ctx.rs_db_dates = db::RecordedStepsDailyDatabase::list_dates();
+        ctx.rs_sync_status = format!("Retrieved {} recorded step entries", history.entries.len());
+    }
+    egui_ctx.request_repaint();
     Ok(())
 }
 
+// Only the display formats are confirmed by the watch (echoing them back), user info gets no response at all
+async fn push_profile(watch: &ls02::Watch<Peripheral>, profile: &db::WatchProfile, watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context) -> ls02::Result<()> {
+    watch_ctx.lock().unwrap().profile_status = "Sending profile...".to_string();
+    egui_ctx.request_repaint();
+
+    let res = async {
+        watch.set_user_info(profile.user_info().build()?).await?;
+        watch.set_display_formats(profile.distance_unit, profile.time_fmt).await
+    }.await;
+
+    watch_ctx.lock().unwrap().profile_status = match &res {
+        Ok(()) => format!("Profile sent, display formats confirmed by the watch ({:?}, {:?})", profile.distance_unit, profile.time_fmt),
+        Err(ls02::Error::UnexpectedResponse(ls02::Response::ConfigDisplayFormats(echo))) => format!("Profile sent, but the watch echoed different display formats ({:?}, {:?})", echo.distance_unit, echo.time_fmt),
+        Err(ls02::Error::Timeout(_)) => "Profile sent, but the watch did not confirm the display formats".to_string(),
+        Err(err) => format!("Unable to send profile: {}", err)
+    };
+    egui_ctx.request_repaint();
+    res
+}
+
 impl MainApp {
     pub fn new() -> Self {
         Self {
             started: false,
             ctx: Arc::new(Mutex::new(WatchContext::new())),
+            title: String::new(),
             text: String::new(),
             text_type: AlertType::Message,
+            text_transliterate: true,
             cur_hr_db_date_idx: 0,
             cur_hr_db_date_chart_bars: None,
             cur_hr_db_day_entry: None,
             cur_rs_db_date_idx: 0,
+            profile: db::WatchProfile::load(),
             show_msg_window: false,
             show_hr_window: false,
-            show_rs_window: false
+            show_rs_window: false,
+            show_settings_window: false
         }
     }
 
@@ -84,7 +235,12 @@ impl MainApp {
             .open(&mut self.show_msg_window)
             .resizable(true)
             .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Title (optional):");
+                    ui.text_edit_singleline(&mut self.title);
+                });
                 ui.text_edit_multiline(&mut self.text);
+                ui.checkbox(&mut self.text_transliterate, "Replace characters the watch can't display");
                 egui::ComboBox::from_label("Message type")
                     .selected_text(format!("{:?}", self.text_type))
                     .show_ui(ui, |ui| {
@@ -118,12 +274,28 @@ impl MainApp {
                         foreach!(AlertType::Pinterest);
                         foreach!(AlertType::YouTube);
                     });
-                if ui.button("Send").clicked() {
-                    let per = watch_ctx.peripheral.clone().unwrap();
-                    let text_c = self.text.clone();
-                    let text_type_c = self.text_type.clone();
+
+                let mut msg = AlertMessage::new(self.text_type, &self.text)
+                    .with_truncation(TruncationPolicy::Ellipsis)
+                    .with_transliteration(self.text_transliterate);
+                if !self.title.is_empty() {
+                    msg = msg.with_title(&self.title);
+                }
+
+                let full_len = msg.full_text().encode_utf16().count();
+                if full_len > msg.max_len {
+                    ui.label(format!("Length: {}/{} (will be truncated)", full_len, msg.max_len));
+                }
+                else {
+                    ui.label(format!("Length: {}/{}", full_len, msg.max_len));
+                }
+
+                if ui.add_enabled(watch_ctx.watch.is_some(), egui::Button::new("Send")).clicked() {
+                    let watch = watch_ctx.watch.clone().unwrap();
                     tokio::spawn(async move {
-                        ls02::general::req::write_send_alert(&per, text_type_c, text_c).await.unwrap()
+                        if let Err(err) = watch.send_alert_message(&msg).await {
+                            log_send_error(err);
+                        }
                     });
                 }
             });
@@ -131,18 +303,23 @@ impl MainApp {
     }
 
     fn hr_window(&mut self, ctx: &Context) {
+        let watch_ctx_c = self.ctx.clone();
         if let Ok(watch_ctx) = self.ctx.lock() {
             egui::Window::new("Heart rate data")
             .open(&mut self.show_hr_window)
             .resizable(true)
             .show(ctx, |ui| {
-                if ui.button("Retrieve heart rate data").clicked() {
+                if ui.add_enabled(watch_ctx.watch.is_some(), egui::Button::new("Retrieve heart rate data")).clicked() {
                     println!("{:?}", db::HeartRateDailyDatabase::list_dates());
-                    let per = watch_ctx.peripheral.clone().unwrap();
+                    let watch = watch_ctx.watch.clone().unwrap();
+                    let egui_ctx_c = ctx.clone();
                     tokio::spawn(async move {
-                        request_hr_data(&per).await.unwrap()
+                        if let Err(err) = sync_hr_data(&watch, &watch_ctx_c, &egui_ctx_c).await {
+                            log_send_error(err);
+                        }
                     });
                 }
+                ui.label(&watch_ctx.hr_sync_status);
 
                 ui.separator();
 
@@ -172,7 +349,7 @@ impl MainApp {
                 }
 
                 if let Some(hr_chart) = self.cur_hr_db_date_chart_bars.as_ref() {
-                    let x_fmt = |y: GridMark, x, _range: &RangeInclusive<f64>| {
+                    let x_fmt = |y: GridMark, _x, _range: &RangeInclusive<f64>| {
                         let real_x = y.value as u32;
                         let min = real_x % 60;
                         let hour = real_x / 60;
@@ -182,7 +359,7 @@ impl MainApp {
                     Plot::new("hr_plot")
                         .x_axis_formatter(x_fmt)
                         .show(ui, |plot_ui| {
-                            let elm_fmt = |a: &Bar, b: &BarChart| {
+                            let elm_fmt = |a: &Bar, _b: &BarChart| {
                                 let real_x = a.argument as u32;
                                 let min = real_x % 60;
                                 let hour = real_x / 60;
@@ -206,18 +383,23 @@ impl MainApp {
     }
 
     fn rs_window(&mut self, ctx: &Context) {
+        let watch_ctx_c = self.ctx.clone();
         if let Ok(watch_ctx) = self.ctx.lock() {
             egui::Window::new("Recorded step data")
             .open(&mut self.show_rs_window)
             .resizable(true)
             .show(ctx, |ui| {
-                if ui.button("Retrieve recorded step data").clicked() {
+                if ui.add_enabled(watch_ctx.watch.is_some(), egui::Button::new("Retrieve recorded step data")).clicked() {
                     println!("{:?}", db::RecordedStepsDailyDatabase::list_dates());
-                    let per = watch_ctx.peripheral.clone().unwrap();
+                    let watch = watch_ctx.watch.clone().unwrap();
+                    let egui_ctx_c = ctx.clone();
                     tokio::spawn(async move {
-                        request_rs_data(&per).await.unwrap()
+                        if let Err(err) = sync_rs_data(&watch, &watch_ctx_c, &egui_ctx_c).await {
+                            log_send_error(err);
+                        }
                     });
                 }
+                ui.label(&watch_ctx.rs_sync_status);
 
                 ui.separator();
 
@@ -249,7 +431,7 @@ impl MainApp {
                 }
 
                 if let Some(hr_chart) = self.cur_hr_db_date_chart_bars.as_ref() {
-                    let x_fmt = |y: GridMark, x, _range: &RangeInclusive<f64>| {
+                    let x_fmt = |y: GridMark, _x, _range: &RangeInclusive<f64>| {
                         let real_x = y.value as u32;
                         let min = real_x % 60;
                         let hour = real_x / 60;
@@ -259,7 +441,7 @@ impl MainApp {
                     Plot::new("hr_plot")
                         .x_axis_formatter(x_fmt)
                         .show(ui, |plot_ui| {
-                            let elm_fmt = |a: &Bar, b: &BarChart| {
+                            let elm_fmt = |a: &Bar, _b: &BarChart| {
                                 let real_x = a.argument as u32;
                                 let min = real_x % 60;
                                 let hour = real_x / 60;
@@ -281,6 +463,99 @@ impl MainApp {
             });
         }
     }
+
+    fn settings_window(&mut self, ctx: &Context) {
+        let watch_ctx_c = self.ctx.clone();
+        if let Ok(watch_ctx) = self.ctx.lock() {
+            egui::Window::new("Watch settings")
+            .open(&mut self.show_settings_window)
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Height (cm):");
+                    ui.add(egui::DragValue::new(&mut self.profile.height_cm).clamp_range(UserInfo::HEIGHT_CM_RANGE));
+                    ui.end_row();
+
+                    ui.label("Weight (kg):");
+                    ui.add(egui::DragValue::new(&mut self.profile.weight_kg).clamp_range(UserInfo::WEIGHT_KG_RANGE));
+                    ui.end_row();
+
+                    ui.label("Age:");
+                    ui.add(egui::DragValue::new(&mut self.profile.age).clamp_range(UserInfo::AGE_RANGE));
+                    ui.end_row();
+
+                    ui.label("Gender:");
+                    egui::ComboBox::from_id_source("settings_gender")
+                        .selected_text(format!("{:?}", self.profile.gender))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.profile.gender, UserGender::Male, "Male");
+                            ui.selectable_value(&mut self.profile.gender, UserGender::Female, "Female");
+                        });
+                    ui.end_row();
+
+                    ui.label("Step goal:");
+                    ui.add(egui::DragValue::new(&mut self.profile.step_goal).clamp_range(UserInfo::STEP_GOAL_RANGE).speed(100));
+                    ui.end_row();
+
+                    ui.label("Screen timeout (seconds):");
+                    ui.add(egui::DragValue::new(&mut self.profile.screen_show_timeout_seconds).clamp_range(UserInfo::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE));
+                    ui.end_row();
+
+                    ui.label("Lift wrist to wake:");
+                    egui::ComboBox::from_id_source("settings_lift_wrist_mode")
+                        .selected_text(format!("{:?}", self.profile.lift_wrist_mode))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.profile.lift_wrist_mode, UserLiftWristMode::On, "On");
+                            ui.selectable_value(&mut self.profile.lift_wrist_mode, UserLiftWristMode::Off, "Off");
+                        });
+                    ui.end_row();
+
+                    ui.label("Units:");
+                    egui::ComboBox::from_id_source("settings_distance_unit")
+                        .selected_text(format!("{:?}", self.profile.distance_unit))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.profile.distance_unit, DistanceUnit::Metric, "Metric");
+                            ui.selectable_value(&mut self.profile.distance_unit, DistanceUnit::Imperial, "Imperial");
+                        });
+                    ui.end_row();
+
+                    ui.label("Time format:");
+                    egui::ComboBox::from_id_source("settings_time_fmt")
+                        .selected_text(match self.profile.time_fmt { TimeFormat::H24 => "24h", TimeFormat::H12 => "12h" })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.profile.time_fmt, TimeFormat::H24, "24h");
+                            ui.selectable_value(&mut self.profile.time_fmt, TimeFormat::H12, "12h");
+                        });
+                    ui.end_row();
+                });
+
+                ui.separator();
+
+                match self.profile.user_info().validate() {
+                    Ok(()) => {
+                        if ui.button("Save and send").clicked() {
+                            self.profile.save();
+
+                            // Otherwise it will be pushed on the next connection
+                            if let Some(watch) = watch_ctx.watch.clone() {
+                                let profile = self.profile.clone();
+                                let egui_ctx_c = ctx.clone();
+                                tokio::spawn(async move {
+                                    if let Err(err) = push_profile(&watch, &profile, &watch_ctx_c, &egui_ctx_c).await {
+                                        log_send_error(err);
+                                    }
+                                });
+                            }
+                        }
+                    },
+                    Err(err) => {
+                        ui.label(format!("Invalid settings: {}", err));
+                    }
+                }
+                ui.label(&watch_ctx.profile_status);
+            });
+        }
+    }
 }
 
 impl eframe::App for MainApp {
@@ -289,7 +564,9 @@ impl eframe::App for MainApp {
             let watch_ctx_c = self.ctx.clone();
             let egui_ctx_c = ctx.clone();
             tokio::spawn(async move {
-                watch_discover_thread(watch_ctx_c, egui_ctx_c).await.unwrap()
+                if let Err(err) = watch_connection_thread(watch_ctx_c, egui_ctx_c).await {
+                    eprintln!("[haywatch.Connection] ERROR: {}", err);
+                }
             });
             
             self.started = true;
@@ -298,9 +575,9 @@ impl eframe::App for MainApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical(|ui| {
                 if let Ok(ctx) = self.ctx.lock() {
-                    if ctx.is_connected {
-                        ui.label("LS02 (connected)");
+                    ui.label(format!("LS02: {}", ctx.state));
 
+                    if ctx.state.is_usable() {
                         ui.separator();
 
                         ui.label(if let Some(battery) = ctx.battery { format!("Battery: {}%", battery) } else { "<unk battery>".to_string() });
@@ -309,6 +586,19 @@ impl eframe::App for MainApp {
 
                         ui.separator();
 
+                        if let Some(forecast) = ctx.forecast.as_ref() {
+                            ui.label(format!("Weather: {} ({}°C now)", day_weather_text(&forecast.today), forecast.cur_temperature));
+                            for (i, day) in forecast.next.iter().enumerate() {
+                                ui.label(format!("In {} days: {}", i + 1, day_weather_text(day)));
+                            }
+                        }
+                        else {
+                            ui.label("<no weather pushed>");
+                        }
+                        ui.label(&ctx.weather_status);
+
+                        ui.separator();
+
                         if ui.button("Message sender").clicked() {
                             self.show_msg_window = true;
                         }
@@ -318,9 +608,9 @@ impl eframe::App for MainApp {
                         if ui.button("Recorded step data viewer").clicked() {
                             self.show_rs_window = true;
                         }
-                    }
-                    else {
-                        ui.label("LS02 NOT connected...");
+                        if ui.button("Watch settings").clicked() {
+                            self.show_settings_window = true;
+                        }
                     }
                 }
             });
@@ -329,224 +619,225 @@ impl eframe::App for MainApp {
         self.msg_window(ctx);
         self.hr_window(ctx);
         self.rs_window(ctx);
+        self.settings_window(ctx);
     }
 }
 
-const WATCH_PAIR_KEY: ls02::common::PairKey = [1, 2, 3, 4];
-
-async fn watch_initial_send_thread(watch: Peripheral, watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context) -> Result<(), Box<dyn Error>> {
-    watch_ctx.lock().unwrap().peripheral = Some(watch.clone());
+fn log_send_error(err: ls02::Error) {
+    match err {
+        ls02::Error::Transport(_) | ls02::Error::NotificationStreamClosed => eprintln!("[haywatch.Send] ERROR: Lost connection with the watch ({})", err),
+        ls02::Error::Timeout(_) => eprintln!("[haywatch.Send] ERROR: The watch did not respond ({})", err),
+        err => eprintln!("[haywatch.Send] ERROR: {}", err)
+    }
+}
 
-    // First of all, pair
-    ls02::write::<ls02::general::req::PairRequest>(&watch,ls02::general::req::PairRequest::new(WATCH_PAIR_KEY)).await?;
+// Some info is only nice to have: don't give up on the rest if the watch doesn't provide it
+fn optional_info<T>(res: ls02::Result<T>, name: &str) -> ls02::Result<Option<T>> {
+    match res {
+        Ok(info) => Ok(Some(info)),
+        Err(err @ (ls02::Error::Timeout(_) | ls02::Error::Decode { .. } | ls02::Error::TrailingBytes { .. } | ls02::Error::UnexpectedResponse(_))) => {
+            eprintln!("[haywatch.Send] Unable to get {}: {}", name, err);
+            Ok(None)
+        },
+        Err(err) => Err(err)
+    }
+}
 
-    // Retrieve the key used to pair (just in case, but not necessary)
-    ls02::write::<ls02::general::req::PairKeyRequest>(&watch, ls02::general::req::PairKeyRequest::new()).await?;
+fn day_weather_text(day: &DayWeather) -> String {
+    format!("{:?}, {}°C/{}°C", day.weather_type, day.max_temperature, day.min_temperature)
+}
 
-    // Get current battery
-    ls02::write::<ls02::general::req::BatteryRequest>(&watch, ls02::general::req::BatteryRequest::new()).await?;
+// Configured with --weather-file <JSON/TOML file> or --weather-lat/--weather-lon (plus --weather-url for a different Open-Meteo server), refreshed every --weather-interval minutes
+fn weather_config() -> Result<Option<weather::WeatherConfig>, Box<dyn Error>> {
+    let refresh_interval = match arg_value("--weather-interval") {
+        Some(interval_mins) => Duration::from_secs(interval_mins.parse::<u64>()? * 60),
+        None => weather::DEFAULT_REFRESH_INTERVAL
+    };
+
+    if let Some(path) = arg_value("--weather-file") {
+        return Ok(Some(weather::WeatherConfig {
+            provider: Arc::new(weather::FileProvider::new(path.into())),
+            refresh_interval
+        }));
+    }
 
-    // Get watch firmware
-    ls02::write::<ls02::general::req::FirmwareRequest>(&watch, ls02::general::req::FirmwareRequest::new()).await?;
+    match (arg_value("--weather-lat"), arg_value("--weather-lon")) {
+        (Some(latitude), Some(longitude)) => {
+            let endpoint = arg_value("--weather-url").unwrap_or_else(|| weather::DEFAULT_OPEN_METEO_ENDPOINT.to_string());
+            Ok(Some(weather::WeatherConfig {
+                provider: Arc::new(weather::OpenMeteoProvider::new(endpoint, latitude.parse()?, longitude.parse()?)),
+                refresh_interval
+            }))
+        },
+        _ => Ok(None)
+    }
+}
 
-    // Heart rate data
-    request_hr_data(&watch).await?;
-    
-    // Recorded steps data
-    request_rs_data(&watch).await?;
+// Pushes the forecast right after (re)connecting and then periodically, until the watch disconnects
+// Runs until aborted along with the rest of the session's tasks on disconnection
+async fn weather_thread(watch: ls02::Watch<Peripheral>, watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context, config: weather::WeatherConfig) {
+    let provider = config.provider;
+    loop {
+        let provider_c = provider.clone();
+        let fetch_res = tokio::task::spawn_blocking(move || provider_c.fetch()).await.unwrap_or_else(|err| Err(err.into()));
+
+        match fetch_res {
+            Ok(forecast) => match watch.set_forecast(&forecast).await {
+                Ok(()) => {
+                    let mut ctx = watch_ctx.lock().unwrap();
+                    ctx.forecast = Some(forecast);
+                    ctx.weather_status = format!("Weather from {} pushed at {}", provider.name(), chrono::Local::now().format("%H:%M"));
+                },
+                Err(err) => {
+                    watch_ctx.lock().unwrap().weather_status = format!("Unable to push weather: {}", err);
+                    log_send_error(err);
+                }
+            },
+            Err(err) => {
+                eprintln!("[haywatch.Weather] ERROR: Unable to get weather from {}: {}", provider.name(), err);
+                watch_ctx.lock().unwrap().weather_status = format!("Unable to get weather from {}: {}", provider.name(), err);
+            }
+        }
+        egui_ctx.request_repaint();
 
-    Ok(())
+        time::sleep(config.refresh_interval).await;
+    }
 }
 
-async fn watch_receive_thread(watch: Peripheral, watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context, notif_char: Characteristic, name: &str) -> Result<(), Box<dyn Error>> {
-    watch.subscribe(&notif_char).await?;
+fn set_state(watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context, state: ConnectionState) {
+    println!("[haywatch.Connection] LS02: {}", state);
+    watch_ctx.lock().unwrap().state = state;
+    egui_ctx.request_repaint();
+}
 
-    let mut notif_stream = watch.notifications().await?;
-    loop {
-        if let Some(value) = notif_stream.next().await {
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::general::res::BatteryResponse>(&value, &notif_char).await {
-                watch_ctx.lock().unwrap().battery = Some(res.battery_percentage);
-                egui_ctx.request_repaint();
-            }
+// Everything done once connected, staying connected afterwards (the spawned threads being added to tasks, so that they can be stopped on disconnection)
+async fn watch_session(watch: &ls02::Watch<Peripheral>, watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context, backoff: &mut connection::Backoff, tasks: &mut Vec<tokio::task::JoinHandle<()>>) -> ls02::Result<()> {
+    // First of all, pair (with this watch's own key, checking that the watch accepted it)
+    set_state(watch_ctx, egui_ctx, ConnectionState::Pairing);
+    let pair_key = watch.pair_with_store(watch.transport().address(), &mut db::pair_key_store()).await?;
+    watch_ctx.lock().unwrap().pair_key = Some(pair_key);
+    egui_ctx.request_repaint();
+
+    set_state(watch_ctx, egui_ctx, ConnectionState::Syncing);
+
+    // Then restore our profile and display formats, since the watch might have been reset (or used with something else) meanwhile
+    optional_info(push_profile(watch, &db::WatchProfile::load(), watch_ctx, egui_ctx).await, "profile confirmation")?;
+
+    // Weather is kept up to date meanwhile
+    match weather_config() {
+        Ok(Some(config)) => {
+            let watch_c = watch.clone();
+            let watch_ctx_c = watch_ctx.clone();
+            let egui_ctx_c = egui_ctx.clone();
+            tasks.push(tokio::spawn(weather_thread(watch_c, watch_ctx_c, egui_ctx_c, config)));
+        },
+        Ok(None) => watch_ctx.lock().unwrap().weather_status = "Weather not configured".to_string(),
+        Err(err) => {
+            eprintln!("[haywatch.Weather] ERROR: Invalid weather configuration: {}", err);
+            watch_ctx.lock().unwrap().weather_status = format!("Invalid weather configuration: {}", err);
+        }
+    }
 
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::general::res::PairKeyResponse>(&value, &notif_char).await {
-                watch_ctx.lock().unwrap().pair_key = Some(res.cur_pair_key);
-                egui_ctx.request_repaint();
-            }
-            
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::general::res::FirmwareResponse>(&value, &notif_char).await {
-                watch_ctx.lock().unwrap().firmware = String::from_utf8(res.name.to_vec()).unwrap();
-                egui_ctx.request_repaint();
-            }
+    // Get current battery
+    let battery = optional_info(watch.battery().await, "battery")?;
+    watch_ctx.lock().unwrap().battery = battery;
+    egui_ctx.request_repaint();
 
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::data2::res::HeartRateDataTodayResponse>(&value, &notif_char).await {
-                let mut db = db::HeartRateDailyDatabase::create_load_by_date(res.date);
-
-                let entry = db::HeartRateDailyDatabaseEntry {
-                    hour: res.hour,
-                    min: res.min,
-                    heart_rate: INVALID_HEART_RATE,
-                    max_heart_rate: res.max_heart_rate,
-                    min_heart_rate: res.min_heart_rate,
-                    avg_heart_rate: res.avg_heart_rate
-                };
-                db.push(entry);
-
-                db.save(res.date);
-                if let Ok(mut ctx) = watch_ctx.lock() {
-                    ctx.hr_db_dates = db::HeartRateDailyDatabase::list_dates();
-                }
-            }
+    // Get watch firmware
+    let firmware = optional_info(watch.firmware().await, "firmware")?;
+    watch_ctx.lock().unwrap().firmware = firmware.unwrap_or_default();
+    egui_ctx.request_repaint();
 
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::data2::res::HeartRateDataDayHourEntryResponse>(&value, &notif_char).await {
-                let mut db = db::HeartRateDailyDatabase::create_load_by_date(res.date);
-
-                let mut cur_time = res.hour as u32 * 60;
-                for heart_rate in res.heart_rates {
-                    let entry = db::HeartRateDailyDatabaseEntry {
-                        hour: (cur_time / 60) as u8,
-                        min: (cur_time % 60) as u8,
-                        heart_rate,
-                        max_heart_rate: INVALID_HEART_RATE,
-                        min_heart_rate: INVALID_HEART_RATE,
-                        avg_heart_rate: INVALID_HEART_RATE
-                    };
-                    // println!("Got hourly heart rate entry: {:02}:{:02}", entry.hour, entry.min);
-                    db.push(entry);
+    // Heart rate data (a failed sync is already shown in its status, the watch is still usable)
+    optional_info(sync_hr_data(watch, watch_ctx, egui_ctx).await, "heart rate data")?;
 
-                    // Entries are separated by 10min
-                    cur_time += 10;
-                }
+    // Recorded steps data
+    optional_info(sync_rs_data(watch, watch_ctx, egui_ctx).await, "recorded step data")?;
 
-                db.save(res.date);
-                if let Ok(mut ctx) = watch_ctx.lock() {
-                    ctx.hr_db_dates = db::HeartRateDailyDatabase::list_dates();
-                }
-            }
+    set_state(watch_ctx, egui_ctx, ConnectionState::Connected);
+    backoff.reset();
+    future::pending().await
+}
 
-            if let ls02::CommandResponseResult::Ok(res) = ls02::read::<ls02::data2::res::RecordedStepsEntryResponse>(&value, &notif_char).await {
-                let mut db = db::RecordedStepsDailyDatabase::create_load_by_date(res.entry.date);
+// Handles everything the watch sends on its own
+async fn watch_receive_thread(watch: ls02::Watch<Peripheral>, watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context) -> ls02::Result<()> {
+    let mut battery_stream = watch.battery_stream().await?;
+    while let Some(battery) = battery_stream.next().await {
+        watch_ctx.lock().unwrap().battery = Some(battery);
+        egui_ctx.request_repaint();
+    }
 
-                let new_walk_step_count = res.entry.new_walk_step_count_be.swap_bytes();
-                if new_walk_step_count > 0 {
-                    let entry = db::RecordedStepsDailyDatabaseEntry {
-                        hour: res.entry.hour,
-                        min: res.entry.last_new_walk_step_min,
-                        new_step_kind: db::RecordedStepKind::Walk,
-                        new_step_count: new_walk_step_count
-                    };
+    Ok(())
+}
 
-                    println!("Walked {} steps at {:?} at {:02}:{:02}", new_walk_step_count, res.entry.date, entry.hour, entry.min);
-                    db.push(entry);
-                }
+const SCAN_TIMEOUT: Duration = Duration::from_secs(10);
 
-                let new_run_step_count = res.entry.new_run_step_count_be.swap_bytes();
-                if new_run_step_count > 0 {
-                    let entry = db::RecordedStepsDailyDatabaseEntry {
-                        hour: res.entry.hour,
-                        min: res.entry.last_new_run_step_min,
-                        new_step_kind: db::RecordedStepKind::Run,
-                        new_step_count: new_run_step_count
-                    };
+// Value following a "--name" argument, if present
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
 
-                    println!("Ran {} steps at {:?} at {:02}:{:02}", new_run_step_count, res.entry.date, entry.hour, entry.min);
-                    db.push(entry);
-                }
+// A single connection, from scanning until the watch disconnects (Ok) or something fails
+async fn watch_connection(adapter: &Adapter, address: Option<BDAddr>, watch_ctx: &Arc<Mutex<WatchContext>>, egui_ctx: &egui::Context, backoff: &mut connection::Backoff) -> ls02::Result<()> {
+    set_state(watch_ctx, egui_ctx, ConnectionState::Scanning);
+    let candidate = ls02::discover::find(adapter, address, SCAN_TIMEOUT).await?;
+    println!("[haywatch.Connection] Found LS02 '{}'! (already connected: {}) --- MAC: {}, RSSI: {:?}", candidate.name.as_deref().unwrap_or(ls02::DEVICE_NAME), candidate.is_connected, candidate.address, candidate.rssi);
+
+    // Listen before connecting, so that no disconnection is missed
+    let mut events: connection::CentralEventStream = adapter.events().await?;
+
+    set_state(watch_ctx, egui_ctx, ConnectionState::Connecting);
+    let watch = candidate.connect().await?;
+    let id = watch.transport().id();
+    watch_ctx.lock().unwrap().watch = Some(watch.clone());
+
+    let watch_c = watch.clone();
+    let watch_ctx_c = watch_ctx.clone();
+    let egui_ctx_c = egui_ctx.clone();
+    let mut tasks = vec![tokio::spawn(async move {
+        if let Err(err) = watch_receive_thread(watch_c, watch_ctx_c, egui_ctx_c).await {
+            eprintln!("[haywatch.Receive] ERROR: {}", err);
+        }
+    })];
 
-                db.save(res.entry.date);
-                if let Ok(mut ctx) = watch_ctx.lock() {
-                    ctx.rs_db_dates = db::RecordedStepsDailyDatabase::list_dates();
-                }
-            }
+    let res = tokio::select! {
+        res = watch_session(&watch, watch_ctx, egui_ctx, backoff, &mut tasks) => res,
+        _ = connection::wait_disconnected(&mut events, &id) => {
+            println!("[haywatch.Connection] Watch disconnected...");
+            Ok(())
         }
+    };
+
+    for task in tasks {
+        task.abort();
     }
-}
+    watch_ctx.lock().unwrap().watch = None;
 
-async fn watch_discover_thread(watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context) -> Result<(), Box<dyn Error>> {
-    let manager = Manager::new().await?;
-    let adapter_list = manager.adapters().await?;
-    if adapter_list.is_empty() {
-        panic!("No Bluetooth adapters found...");
+    // After failures the watch might still be connected
+    if let Err(err) = watch.transport().disconnect().await {
+        eprintln!("[haywatch.Connection] ERROR: Unable to disconnect: {}", err);
     }
+    res
+}
 
+// Connects (again and again) to the watch, waiting longer after each failed attempt
+async fn watch_connection_thread(watch_ctx: Arc<Mutex<WatchContext>>, egui_ctx: egui::Context) -> Result<(), Box<dyn Error>> {
+    let adapter = ls02::discover::select_adapter(arg_value("--adapter").as_deref()).await?;
+    let address = arg_value("--address").map(|address| address.parse::<BDAddr>()).transpose()?;
+
+    let mut backoff = connection::Backoff::new();
     loop {
-        let res: Result<(), Box<dyn Error>> = {
-            for adapter in adapter_list.iter() {
-                println!("Starting scan...");
-                let scan_filter = ScanFilter {
-                    services: vec![ls02::SERVICE_GENERAL, ls02::SERVICE_DATA2]
-                };
-                adapter.start_scan(scan_filter).await.expect("Can't scan BLE adapter for connected devices...");
-    
-                time::sleep(Duration::from_secs(2)).await;
-    
-                let peripherals = adapter.peripherals().await?;
-                if peripherals.is_empty() {
-                    eprintln!("[haywatch.Discover] ERROR: BLE peripheral devices were not found...");
-                }
-                else {
-                    for peripheral in &peripherals {
-                        let properties = peripheral.properties().await?;
-                        let is_connected = peripheral.is_connected().await?;
-
-                        match &properties.as_ref().unwrap().local_name {
-                            Some(name) => {
-                                if name == ls02::DEVICE_NAME {
-                                    println!("[haywatch.Discover] Found LS02 '{}'! (already connected: {}) --- MAC: {}", name, is_connected, properties.as_ref().unwrap().address);
-        
-                                    let is_connected = peripheral.is_connected().await?;
-                                    if !is_connected {
-                                        peripheral.connect().await?;
-                                    }
-        
-                                    println!("[haywatch.Discover] Discovering services...");
-                                    peripheral.discover_services().await?;
-        
-                                    watch_ctx.lock().unwrap().is_connected = true;
-                                    egui_ctx.request_repaint();
-        
-                                    let peripheral_c = peripheral.clone();
-                                    let watch_ctx_c = watch_ctx.clone();
-                                    let egui_ctx_c = egui_ctx.clone();
-                                    tokio::spawn(async move {
-                                        watch_receive_thread(peripheral_c, watch_ctx_c, egui_ctx_c, ls02::CHAR_GENERAL_N_1, "General").await.unwrap()
-                                    });
-        
-                                    let peripheral_c = peripheral.clone();
-                                    let watch_ctx_c = watch_ctx.clone();
-                                    let egui_ctx_c = egui_ctx.clone();
-                                    tokio::spawn(async move {
-                                        watch_receive_thread(peripheral_c, watch_ctx_c, egui_ctx_c, ls02::CHAR_DATA2_N, "Data2").await.unwrap()
-                                    });
-        
-                                    let peripheral_c = peripheral.clone();
-                                    let watch_ctx_c = watch_ctx.clone();
-                                    let egui_ctx_c = egui_ctx.clone();
-                                    tokio::spawn(async move {
-                                        watch_initial_send_thread(peripheral_c, watch_ctx_c, egui_ctx_c).await.unwrap()
-                                    });
-        
-                                    while peripheral.is_connected().await? {}
-        
-                                    println!("[haywatch.Main] Watch disconnected...");
-                                    peripheral.disconnect().await?;
-                                    break;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+        if let Err(err) = watch_connection(&adapter, address, &watch_ctx, &egui_ctx, &mut backoff).await {
+            match err {
+                ls02::Error::DeviceNotFound => eprintln!("[haywatch.Connection] ERROR: LS02 was not found"),
+                err => log_send_error(err)
             }
-    
-            Ok(())
-        };
-        if res.is_ok() {
-            break;
         }
-    }
 
-    Ok(())
+        let delay = backoff.next_delay();
+        set_state(&watch_ctx, &egui_ctx, ConnectionState::Backoff(delay));
+        time::sleep(delay).await;
+    }
 }
 
 #[tokio::main]
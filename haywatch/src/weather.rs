@@ -0,0 +1,276 @@
+use chrono::Timelike;
+use serde::Deserialize;
+use std::{error::Error, path::PathBuf, sync::Arc, time::Duration};
+use ls02::common::WeatherType;
+use ls02::general::forecast::{is_night_hour, DayWeather, Forecast, TemperatureUnit};
+
+pub type WeatherResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+pub const DEFAULT_OPEN_METEO_ENDPOINT: &str = "https://api.open-meteo.com/v1/forecast";
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+// Fetching is blocking (call it from a blocking task)
+pub trait WeatherProvider: Send + Sync {
+    fn name(&self) -> String;
+
+    fn fetch(&self) -> WeatherResult<Forecast>;
+}
+
+#[derive(Clone)]
+pub struct WeatherConfig {
+    pub provider: Arc<dyn WeatherProvider>,
+    pub refresh_interval: Duration
+}
+
+// The watch's "unknown" weather (0) would make it ignore all the temperatures too, so codes it has no icon for are rejected instead
+fn weather_type(wmo_code: u8, is_night: bool) -> WeatherResult<WeatherType> {
+    WeatherType::from_wmo_code(wmo_code, is_night).ok_or_else(|| format!("Unsupported WMO weather code {}", wmo_code).into())
+}
+
+// Today plus the following three days
+const FORECAST_DAYS: usize = 4;
+
+fn make_forecast(cur_temperature: f32, is_night: bool, days: &[(u8, f32, f32)], unit: TemperatureUnit) -> WeatherResult<Forecast> {
+    if days.len() < FORECAST_DAYS {
+        return Err(format!("Forecast has {} days, {} are needed", days.len(), FORECAST_DAYS).into());
+    }
+
+    // Night variants only make sense for today
+    let day_weather = |day_idx: usize| -> WeatherResult<DayWeather> {
+        let (wmo_code, max_temperature, min_temperature) = days[day_idx];
+        Ok(DayWeather::from_unit(weather_type(wmo_code, is_night && (day_idx == 0))?, max_temperature, min_temperature, unit))
+    };
+    Ok(Forecast::new(unit.to_celsius(cur_temperature), day_weather(0)?, [day_weather(1)?, day_weather(2)?, day_weather(3)?]))
+}
+
+// Anything serving the Open-Meteo forecast API (the real one, a local stand-in server...)
+pub struct OpenMeteoProvider {
+    pub endpoint: String,
+    pub latitude: f64,
+    pub longitude: f64
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoCurrent {
+    temperature_2m: f32,
+    is_day: u8
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoDaily {
+    weather_code: Vec<u8>,
+    temperature_2m_max: Vec<f32>,
+    temperature_2m_min: Vec<f32>
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoResponse {
+    current: OpenMeteoCurrent,
+    daily: OpenMeteoDaily
+}
+
+impl OpenMeteoProvider {
+    pub fn new(endpoint: String, latitude: f64, longitude: f64) -> Self {
+        Self {
+            endpoint,
+            latitude,
+            longitude
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}?latitude={}&longitude={}&current=temperature_2m,is_day&daily=weather_code,temperature_2m_max,temperature_2m_min&forecast_days={}&timezone=auto", self.endpoint, self.latitude, self.longitude, FORECAST_DAYS)
+    }
+}
+
+impl WeatherProvider for OpenMeteoProvider {
+    fn name(&self) -> String {
+        format!("Open-Meteo ({})", self.endpoint)
+    }
+
+    fn fetch(&self) -> WeatherResult<Forecast> {
+        let body = ureq::get(&self.url()).timeout(Duration::from_secs(10)).call()?.into_string()?;
+        let res: OpenMeteoResponse = serde_json::from_str(&body)?;
+
+        let days: Vec<(u8, f32, f32)> = res.daily.weather_code.iter().zip(res.daily.temperature_2m_max.iter()).zip(res.daily.temperature_2m_min.iter())
+            .map(|((&wmo_code, &max_temperature), &min_temperature)| (wmo_code, max_temperature, min_temperature))
+            .collect();
+        make_forecast(res.current.temperature_2m, res.current.is_day == 0, &days, TemperatureUnit::Celsius)
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum FileTemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit
+}
+
+#[derive(Deserialize)]
+struct FileDay {
+    weather_code: u8,
+    max_temperature: f32,
+    min_temperature: f32
+}
+
+// Night is guessed from the local time if not specified
+#[derive(Deserialize)]
+struct FileForecast {
+    #[serde(default)]
+    unit: FileTemperatureUnit,
+    current_temperature: f32,
+    is_night: Option<bool>,
+    days: Vec<FileDay>
+}
+
+// Local JSON or TOML file (depending on its extension), read again on every refresh:
+// unit = "celsius" (or "fahrenheit", optional), current_temperature, is_night (optional) and days (today first) with weather_code (WMO), max_temperature and min_temperature
+pub struct FileProvider {
+    pub path: PathBuf
+}
+
+impl FileProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path
+        }
+    }
+}
+
+impl WeatherProvider for FileProvider {
+    fn name(&self) -> String {
+        format!("file ({})", self.path.display())
+    }
+
+    fn fetch(&self) -> WeatherResult<Forecast> {
+        let contents = std::fs::read_to_string(&self.path)?;
+        let file_forecast: FileForecast = match self.path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            _ => serde_json::from_str(&contents)?
+        };
+
+        let unit = match file_forecast.unit {
+            FileTemperatureUnit::Celsius => TemperatureUnit::Celsius,
+            FileTemperatureUnit::Fahrenheit => TemperatureUnit::Fahrenheit
+        };
+        let is_night = file_forecast.is_night.unwrap_or_else(|| is_night_hour(chrono::Local::now().hour() as u8));
+        let days: Vec<(u8, f32, f32)> = file_forecast.days.iter().map(|day| (day.weather_code, day.max_temperature, day.min_temperature)).collect();
+        make_forecast(file_forecast.current_temperature, is_night, &days, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON_FORECAST: &str = r#"{
+        "unit": "fahrenheit",
+        "current_temperature": 50.0,
+        "is_night": true,
+        "days": [
+            { "weather_code": 61, "max_temperature": 59.0, "min_temperature": 41.0 },
+            { "weather_code": 0, "max_temperature": 68.0, "min_temperature": 50.0 },
+            { "weather_code": 3, "max_temperature": 64.4, "min_temperature": 46.4 },
+            { "weather_code": 95, "max_temperature": 60.8, "min_temperature": 44.6 }
+        ]
+    }"#;
+
+    const TOML_FORECAST: &str = r#"
+        current_temperature = -2.4
+        is_night = false
+
+        [[days]]
+        weather_code = 71
+        max_temperature = 1.0
+        min_temperature = -6.0
+
+        [[days]]
+        weather_code = 45
+        max_temperature = 3.0
+        min_temperature = -1.0
+
+        [[days]]
+        weather_code = 64
+        max_temperature = 2.0
+        min_temperature = -3.0
+
+        [[days]]
+        weather_code = 0
+        max_temperature = 5.0
+        min_temperature = 0.0
+    "#;
+
+    // Each test uses its own file, since tests run in parallel
+    fn fetch_file(name: &str, contents: &str) -> WeatherResult<Forecast> {
+        let path = std::env::temp_dir().join(format!("haywatch-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        let forecast = FileProvider::new(path.clone()).fetch();
+        std::fs::remove_file(&path).unwrap();
+        forecast
+    }
+
+    #[test]
+    fn forecast_days() {
+        let days = [(0, 20.0, 10.0), (61, 15.0, 5.0), (3, 18.0, 8.0), (95, 16.0, 7.0), (0, 25.0, 15.0)];
+        let forecast = make_forecast(12.4, true, &days, TemperatureUnit::Celsius).unwrap();
+
+        // Only today gets the night variant, and extra days are ignored
+        assert_eq!(forecast, Forecast::new(12, DayWeather::new(WeatherType::Night, 20, 10), [
+            DayWeather::new(WeatherType::SlighlyRainy, 15, 5),
+            DayWeather::new(WeatherType::SunnyCloudy, 18, 8),
+            DayWeather::new(WeatherType::Stormy, 16, 7)
+        ]));
+
+        assert!(make_forecast(12.4, false, &days[..3], TemperatureUnit::Celsius).is_err());
+        assert!(make_forecast(12.4, false, &[], TemperatureUnit::Celsius).is_err());
+    }
+
+    #[test]
+    fn json_file() {
+        let forecast = fetch_file("forecast.json", JSON_FORECAST).unwrap();
+
+        assert_eq!(forecast, Forecast::new(10, DayWeather::new(WeatherType::RainyNight, 15, 5), [
+            DayWeather::new(WeatherType::Sunny, 20, 10),
+            DayWeather::new(WeatherType::SunnyCloudy, 18, 8),
+            DayWeather::new(WeatherType::Stormy, 16, 7)
+        ]));
+    }
+
+    #[test]
+    fn toml_file() {
+        let forecast = fetch_file("forecast.toml", TOML_FORECAST).unwrap();
+
+        // Celsius by default
+        assert_eq!(forecast, Forecast::new(-2, DayWeather::new(WeatherType::Snowy, 1, -6), [
+            DayWeather::new(WeatherType::Foggy, 3, -1),
+            DayWeather::new(WeatherType::VeryRainy, 2, -3),
+            DayWeather::new(WeatherType::Sunny, 5, 0)
+        ]));
+    }
+
+    #[test]
+    fn unsupported_wmo_code() {
+        let days = [(0, 20.0, 10.0), (62, 15.0, 5.0), (200, 18.0, 8.0), (95, 16.0, 7.0)];
+        let err = make_forecast(12.4, false, &days, TemperatureUnit::Celsius).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported WMO weather code 200");
+
+        let toml_forecast = TOML_FORECAST.replace("weather_code = 64", "weather_code = 4");
+        let err = fetch_file("unsupported-code.toml", &toml_forecast).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported WMO weather code 4");
+    }
+
+    #[test]
+    fn too_few_days() {
+        let three_days = TOML_FORECAST.rsplit_once("[[days]]").unwrap().0;
+        let err = fetch_file("too-few-days.toml", three_days).unwrap_err();
+        assert_eq!(err.to_string(), "Forecast has 3 days, 4 are needed");
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(fetch_file("invalid.json", "{ \"current_temperature\": 10.0 }").is_err());
+        assert!(fetch_file("invalid.toml", JSON_FORECAST).is_err());
+        assert!(FileProvider::new(std::env::temp_dir().join("haywatch-missing.json")).fetch().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use ls02::common::*;
+use ls02::data2::res::{HeartRateDataDayHourEntryResponse, HeartRateDataTodayResponse};
+use super::SimState;
+
+// Synthetic (but deterministic) history, ending at the simulated date/time
+
+fn previous_date(date: Date) -> Date {
+    if date.day() > 1 {
+        Date::new(date.year(), date.month(), date.day() - 1)
+    }
+    else if date.month() > 1 {
+        Date::new(date.year(), date.month() - 1, Date::days_in_month(date.year(), date.month() - 1))
+    }
+    else {
+        Date::new(date.year() - 1, 12, 31)
+    }
+}
+
+// Oldest day first, today last
+fn history_dates(state: &SimState) -> Vec<(Date, u8)> {
+    let mut dates = vec![(state.date, state.hour)];
+    let mut date = state.date;
+    for _ in 1..state.history_days.max(1) {
+        date = previous_date(date);
+        dates.push((date, 23));
+    }
+    dates.reverse();
+    dates
+}
+
+fn synthetic_heart_rate(date: Date, hour: u8, slot: u8) -> u8 {
+    // Nothing measured while (supposedly) sleeping
+    if hour < 6 {
+        INVALID_HEART_RATE
+    }
+    else {
+        60 + ((date.day() as u32 * 7 + hour as u32 * 5 + slot as u32 * 3) % 45) as u8
+    }
+}
+
+pub fn heart_rate_entries(state: &SimState) -> Vec<HeartRateDataDayHourEntryResponse> {
+    let mut entries = Vec::new();
+    for (date, last_hour) in history_dates(state) {
+        // Entries are only sent for even hours, covering 2 hours each
+        for hour in (0..=last_hour).step_by(2) {
+            let mut heart_rates = [INVALID_HEART_RATE; 12];
+            for (slot, heart_rate) in heart_rates.iter_mut().enumerate() {
+                *heart_rate = synthetic_heart_rate(date, hour + (slot as u8 / 6), slot as u8);
+            }
+
+            entries.push(HeartRateDataDayHourEntryResponse {
+                id: CommandId::HeartRate,
+                date,
+                hour,
+                heart_rates
+            });
+        }
+    }
+    entries
+}
+
+pub fn heart_rate_today(state: &SimState) -> HeartRateDataTodayResponse {
+    let heart_rates = heart_rate_entries(state).into_iter()
+        .filter(|entry| entry.date == state.date)
+        .flat_map(|entry| entry.heart_rates)
+        .filter(|heart_rate| *heart_rate != INVALID_HEART_RATE)
+        .collect::<Vec<_>>();
+
+    let (max_heart_rate, min_heart_rate, avg_heart_rate) = if heart_rates.is_empty() {
+        (INVALID_HEART_RATE, INVALID_HEART_RATE, INVALID_HEART_RATE)
+    }
+    else {
+        let sum = heart_rates.iter().map(|heart_rate| *heart_rate as u32).sum::<u32>();
+        (*heart_rates.iter().max().unwrap(), *heart_rates.iter().min().unwrap(), (sum / heart_rates.len() as u32) as u8)
+    };
+
+    HeartRateDataTodayResponse {
+        id: CommandId::HeartRate,
+        sub_id: HeartRateCommandSubId::DataToday,
+        date: state.date,
+        hour: state.hour,
+        min: state.min,
+        max_heart_rate,
+        min_heart_rate,
+        avg_heart_rate
+    }
+}
+
+pub fn steps_entries(state: &SimState) -> Vec<StepsEntry> {
+    let mut entries = Vec::new();
+    for (date, last_hour) in history_dates(state) {
+        // Only hours with new steps are sent
+        for hour in 8..=last_hour.min(21) {
+            let walk_step_count = 200 + ((date.day() as u16 * 31 + hour as u16 * 17) % 900);
+            let run_step_count = if hour % 3 == 0 { 100 + (date.day() as u16 * 13) % 400 } else { 0 };
+
+            entries.push(StepsEntry {
+                date,
+                hour,
+                new_all_step_count: walk_step_count + run_step_count,
+                unk_1: 0,
+                last_new_run_step_min: if run_step_count > 0 { 40 } else { 0 },
+                unk_2: 0,
+                new_run_step_count: run_step_count,
+                unk_3: 0,
+                last_new_walk_step_min: 50,
+                unk_4: 0,
+                new_walk_step_count: walk_step_count
+            });
+        }
+    }
+    entries
+}
@@ -0,0 +1,491 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use binrw::{BinRead, BinReaderExt, BinWrite, BinWriterExt, io::Cursor};
+use btleplug::api::{BDAddr, Characteristic, ValueNotification, WriteType};
+use futures::stream;
+use tokio::sync::broadcast;
+use tokio::time;
+use uuid::Uuid;
+use ls02::common::*;
+use ls02::transport::NotificationStream;
+use ls02::WatchTransport;
+
+pub mod history;
+
+pub mod script;
+
+pub const DEFAULT_FIRMWARE: [u8; 13] = *b"RH208DV000924";
+// Random static address, just needed to tell simulated watches apart (pair key stores, etc.)
+pub const DEFAULT_ADDRESS: [u8; 6] = [0xC2, 0x4C, 0x53, 0x30, 0x32, 0x00];
+
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    pub address: BDAddr,
+    pub firmware: [u8; 13],
+    pub battery: u8,
+    pub pair_key: Option<PairKey>,
+    pub accept_pairing: bool,
+    // Time the (simulated) user takes to accept the pairing dialog
+    pub pairing_delay: Duration,
+    pub history_days: u8,
+    // None behaves like btleplug peripherals (unknown MTU)
+    pub mtu: Option<usize>,
+    // Writes are accepted but never answered (like a busy watch, or one whose notifications get lost)
+    pub silent: bool,
+    // Otherwise only the General recorded steps request is answered, like with some firmwares
+    pub data2_recorded_steps: bool,
+    // Number of pair key requests whose response gets lost, before answering them again
+    pub lost_pair_key_responses: u8
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            address: BDAddr::from(DEFAULT_ADDRESS),
+            firmware: DEFAULT_FIRMWARE,
+            battery: 87,
+            pair_key: None,
+            accept_pairing: true,
+            pairing_delay: Duration::ZERO,
+            history_days: 3,
+            mtu: None,
+            silent: false,
+            data2_recorded_steps: true,
+            lost_pair_key_responses: 0
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimState {
+    pub address: BDAddr,
+    pub firmware: [u8; 13],
+    pub battery: u8,
+    pub pair_key: Option<PairKey>,
+    pub accept_pairing: bool,
+    pub pairing_delay: Duration,
+    // While the pairing dialog is shown, nothing but pairing commands is answered
+    pub pairing_pending: bool,
+    pub history_days: u8,
+    pub mtu: Option<usize>,
+    pub silent: bool,
+    pub data2_recorded_steps: bool,
+    pub lost_pair_key_responses: u8,
+    pub date: Date,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub distance_unit: DistanceUnit,
+    pub time_fmt: TimeFormat,
+    pub user_info: Option<ls02::general::req::SetUserInfoRequest>,
+    pub weather_today: Option<ls02::general::req::SetWeatherTodayRequest>,
+    pub weather_following_days: Option<ls02::general::req::SetWeatherFollowingDaysRequest>,
+    pub heart_rate_enabled: bool,
+    pub last_alert: Option<(AlertType, String)>,
+    pub connected: bool,
+    // Cleared after a reset with power-off, until then the watch can be reconnected after rebooting
+    pub powered_on: bool,
+    pending_alert: Option<(AlertType, Vec<u16>)>,
+    subscribed: BTreeSet<Uuid>
+}
+
+impl SimState {
+    fn new(config: SimConfig) -> Self {
+        // Same state the watch starts with right after the initial pairing
+        Self {
+            address: config.address,
+            firmware: config.firmware,
+            battery: config.battery,
+            pair_key: config.pair_key,
+            accept_pairing: config.accept_pairing,
+            pairing_delay: config.pairing_delay,
+            pairing_pending: false,
+            history_days: config.history_days,
+            mtu: config.mtu,
+            silent: config.silent,
+            data2_recorded_steps: config.data2_recorded_steps,
+            lost_pair_key_responses: config.lost_pair_key_responses,
+            date: Date::new(2020, 1, 1),
+            hour: 8,
+            min: 0,
+            sec: 0,
+            distance_unit: DistanceUnit::Metric,
+            time_fmt: TimeFormat::H24,
+            user_info: None,
+            weather_today: None,
+            weather_following_days: None,
+            heart_rate_enabled: true,
+            last_alert: None,
+            connected: true,
+            powered_on: true,
+            pending_alert: None,
+            subscribed: BTreeSet::new()
+        }
+    }
+}
+
+// In-process LS02 emulator, usable anywhere a real watch (btleplug peripheral) is expected
+#[derive(Clone)]
+pub struct SimWatch {
+    state: Arc<Mutex<SimState>>,
+    notif_tx: broadcast::Sender<ValueNotification>
+}
+
+// Raw bytes of a request/response, as sent over the air (also handy for injecting notifications)
+pub fn encode<T: BinWrite>(value: &T) -> Vec<u8> where for<'a> <T as BinWrite>::Args<'a>: Default {
+    let mut writer = Cursor::new(Vec::new());
+    writer.write_le(value).unwrap();
+    writer.into_inner()
+}
+
+fn decode<T: BinRead>(data: &[u8]) -> Option<T> where for<'a> <T as BinRead>::Args<'a>: Default {
+    Cursor::new(data).read_le::<T>().ok()
+}
+
+impl SimWatch {
+    const NOTIFICATION_CAPACITY: usize = 1024;
+
+    pub fn new(config: SimConfig) -> Self {
+        let (notif_tx, _) = broadcast::channel(Self::NOTIFICATION_CAPACITY);
+        Self {
+            state: Arc::new(Mutex::new(SimState::new(config))),
+            notif_tx
+        }
+    }
+
+    pub fn state(&self) -> SimState {
+        self.state.lock().unwrap().clone()
+    }
+
+    // Also usable to inject arbitrary notifications (only delivered if the characteristic is subscribed)
+    pub fn notify(&self, characteristic: &Characteristic, value: Vec<u8>) {
+        if self.state.lock().unwrap().subscribed.contains(&characteristic.uuid) {
+            // No receivers just means nobody is listening right now, like with a real watch
+            let _ = self.notif_tx.send(ValueNotification {
+                uuid: characteristic.uuid,
+                value
+            });
+        }
+    }
+
+    pub(crate) fn notify_response<T: BinWrite>(&self, characteristic: &Characteristic, res: &T) where for<'a> <T as BinWrite>::Args<'a>: Default {
+        self.notify(characteristic, encode(res));
+    }
+
+    fn handle_general(&self, data: &[u8]) {
+        use ls02::general::req::*;
+        use ls02::general::res::*;
+
+        let id = match decode::<CommandId>(data) {
+            Some(id) => id,
+            None => return
+        };
+
+        if self.state.lock().unwrap().pairing_pending && (id != CommandId::Pair) {
+            return;
+        }
+
+        match id {
+            CommandId::Pair => {
+                if let Some(req) = decode::<PairRequest>(data) {
+                    let mut state = self.state.lock().unwrap();
+                    if state.accept_pairing {
+                        if state.pairing_delay.is_zero() {
+                            state.pair_key = Some(req.pair_key);
+                        }
+                        else {
+                            state.pairing_pending = true;
+
+                            let sim = self.clone();
+                            let pairing_delay = state.pairing_delay;
+                            tokio::spawn(async move {
+                                time::sleep(pairing_delay).await;
+                                let mut state = sim.state.lock().unwrap();
+                                state.pair_key = Some(req.pair_key);
+                                state.pairing_pending = false;
+                            });
+                        }
+                    }
+                }
+                else if decode::<PairKeyRequest>(data).is_some() {
+                    let mut state = self.state.lock().unwrap();
+                    if state.lost_pair_key_responses > 0 {
+                        state.lost_pair_key_responses -= 1;
+                        return;
+                    }
+                }
+                else {
+                    return;
+                }
+
+                // Unpaired watches report unset key bytes as 0xFF
+                let cur_pair_key = self.state.lock().unwrap().pair_key.unwrap_or([0xFF; 4]);
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &PairKeyResponse {
+                    id: CommandId::Pair,
+                    sub_id: PairCommandSubId::CurrentPairKey,
+                    cur_pair_key
+                });
+            },
+            CommandId::Battery => {
+                let battery_percentage = self.state.lock().unwrap().battery;
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &BatteryResponse {
+                    id: CommandId::Battery,
+                    battery_percentage
+                });
+            },
+            CommandId::Firmware => {
+                let name = self.state.lock().unwrap().firmware;
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &FirmwareResponse {
+                    id: CommandId::Firmware,
+                    name
+                });
+            },
+            CommandId::DateTime => {
+                if let Some(req) = decode::<SetDateTimeRequest>(data) {
+                    {
+                        let mut state = self.state.lock().unwrap();
+                        state.date = req.date;
+                        state.hour = req.hour;
+                        state.min = req.min;
+                        state.sec = req.sec;
+                    }
+
+                    self.notify_response(&ls02::CHAR_GENERAL_N_1, &SetDateTimeResponse {
+                        id: CommandId::DateTime,
+                        date: req.date,
+                        hour: req.hour,
+                        min: req.min,
+                        sec: req.sec
+                    });
+                }
+            },
+            CommandId::Config => {
+                if let Some(req) = decode::<ConfigDisplayFormatsRequest>(data) {
+                    {
+                        let mut state = self.state.lock().unwrap();
+                        state.distance_unit = req.distance_unit;
+                        state.time_fmt = req.time_fmt;
+                    }
+
+                    // The watch echoes the request back
+                    self.notify(&ls02::CHAR_GENERAL_N_1, data.to_vec());
+                }
+            },
+            CommandId::Alert => self.handle_alert(data),
+            CommandId::Weather => {
+                let weather_date = if let Some(req) = decode::<SetWeatherTodayRequest>(data) {
+                    self.state.lock().unwrap().weather_today = Some(req);
+                    WeatherDate::Today
+                }
+                else if let Some(req) = decode::<SetWeatherFollowingDaysRequest>(data) {
+                    self.state.lock().unwrap().weather_following_days = Some(req);
+                    WeatherDate::FollowingDays
+                }
+                else {
+                    return;
+                };
+
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &SetWeatherResponse {
+                    id: CommandId::Weather,
+                    weather_date
+                });
+            },
+            CommandId::UserInfo => {
+                // No response is sent for this one
+                if let Some(req) = decode::<SetUserInfoRequest>(data) {
+                    self.state.lock().unwrap().user_info = Some(req);
+                }
+            },
+            CommandId::Reset => {
+                if let Some(req) = decode::<ResetRequest>(data) {
+                    // Settings are lost and the watch disconnects (dropping subscriptions) to reboot/power-off
+                    let mut state = self.state.lock().unwrap();
+                    state.pair_key = None;
+                    state.user_info = None;
+                    state.distance_unit = DistanceUnit::Metric;
+                    state.time_fmt = TimeFormat::H24;
+                    state.connected = false;
+                    state.powered_on = req.sub_id == ResetCommandSubId::ResetAndReboot;
+                    state.subscribed.clear();
+                }
+            },
+            CommandId::RecordedStepsGeneral if decode::<RecordedStepsRequest>(data).is_some() => {
+                let state = self.state();
+                for entry in history::steps_entries(&state) {
+                    self.notify_response(&ls02::CHAR_GENERAL_N_1, &RecordedStepsEntryResponse {
+                        id: CommandId::RecordedStepsGeneral,
+                        entry
+                    });
+                }
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &RecordedStepsEndResponse {
+                    id: CommandId::RecordedStepsGeneral,
+                    sub_id: RecordedStepsCommandSubId::End,
+                    unk: 0
+                });
+            },
+            _ => {}
+        }
+    }
+
+    fn handle_alert(&self, data: &[u8]) {
+        use ls02::general::req::*;
+
+        if let Some(req) = decode::<AlertStartBatchRequest>(data) {
+            self.state.lock().unwrap().pending_alert = Some((req.alert_type, req.msg_start_text_utf16));
+            self.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Alert as u8, req.batch_idx]);
+        }
+        else if let Some(req) = decode::<AlertPushRequest>(data) {
+            let alert = self.state.lock().unwrap().pending_alert.take();
+            if let Some((alert_type, text_utf16)) = alert {
+                let text = String::from_utf16_lossy(&text_utf16);
+                self.state.lock().unwrap().last_alert = Some((alert_type, text));
+                self.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Alert as u8, req.push_ident, 0, (text_utf16.len() * 2) as u8]);
+            }
+        }
+        else if let Some(mut req) = decode::<AlertNextBatchRequest>(data) {
+            if let Some((_, text_utf16)) = self.state.lock().unwrap().pending_alert.as_mut() {
+                text_utf16.append(&mut req.msg_next_text_utf16);
+            }
+            self.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Alert as u8, req.batch_idx]);
+        }
+    }
+
+    fn handle_data2(&self, data: &[u8]) {
+        use ls02::data2::req::*;
+        use ls02::data2::res::*;
+
+        if decode::<HeartRateDataRequest>(data).is_some() {
+            let state = self.state();
+            self.notify_response(&ls02::CHAR_DATA2_N, &history::heart_rate_today(&state));
+            for entry in history::heart_rate_entries(&state) {
+                self.notify_response(&ls02::CHAR_DATA2_N, &entry);
+            }
+            self.notify_response(&ls02::CHAR_DATA2_N, &HeartRateDataEndResponse {
+                id: CommandId::HeartRate,
+                sub_id: HeartRateCommandSubId::DataEnd,
+                unk: 0
+            });
+        }
+        else if decode::<HeartRateEnableRequest>(data).is_some() {
+            self.state.lock().unwrap().heart_rate_enabled = true;
+            self.notify(&ls02::CHAR_DATA2_N, data.to_vec());
+        }
+        else if decode::<HeartRateDisableRequest>(data).is_some() {
+            self.state.lock().unwrap().heart_rate_enabled = false;
+            self.notify(&ls02::CHAR_DATA2_N, data.to_vec());
+        }
+        else if decode::<RecordedStepsRequest>(data).is_some() {
+            let state = self.state();
+            if !state.data2_recorded_steps {
+                return;
+            }
+
+            for entry in history::steps_entries(&state) {
+                self.notify_response(&ls02::CHAR_DATA2_N, &RecordedStepsEntryResponse {
+                    id: CommandId::RecordedStepsData2,
+                    entry
+                });
+            }
+            self.notify_response(&ls02::CHAR_DATA2_N, &RecordedStepsEndResponse {
+                id: CommandId::RecordedStepsData2,
+                sub_id: RecordedStepsCommandSubId::End,
+                unk: 0
+            });
+        }
+    }
+}
+
+impl Default for SimWatch {
+    fn default() -> Self {
+        Self::new(SimConfig::default())
+    }
+}
+
+#[async_trait]
+impl WatchTransport for SimWatch {
+    async fn write(&self, characteristic: &Characteristic, data: &[u8], _write_type: WriteType) -> btleplug::Result<()> {
+        if !self.state.lock().unwrap().connected {
+            return Err(btleplug::Error::NotConnected);
+        }
+
+        // Same as a real BLE stack, writes not fitting in a single ATT packet are rejected
+        let max_write_size = ls02::max_write_size(self).await;
+        if data.len() > max_write_size {
+            return Err(btleplug::Error::Other(format!("Write of {} bytes exceeds the maximum of {} bytes", data.len(), max_write_size).into()));
+        }
+
+        if self.state.lock().unwrap().silent {
+            return Ok(());
+        }
+
+        if characteristic.uuid == ls02::CHAR_GENERAL_RW_1.uuid {
+            self.handle_general(data);
+        }
+        else if characteristic.uuid == ls02::CHAR_DATA2_RW.uuid {
+            self.handle_data2(data);
+        }
+
+        Ok(())
+    }
+
+    async fn read(&self, characteristic: &Characteristic) -> btleplug::Result<Vec<u8>> {
+        if !self.state.lock().unwrap().connected {
+            return Err(btleplug::Error::NotConnected);
+        }
+
+        if characteristic.uuid == ls02::CHAR_BATTERY_LEVEL.uuid {
+            Ok(vec![self.state.lock().unwrap().battery])
+        }
+        else {
+            Err(btleplug::Error::NotSupported(format!("Reading characteristic {}", characteristic.uuid)))
+        }
+    }
+
+    async fn subscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.connected {
+            return Err(btleplug::Error::NotConnected);
+        }
+
+        state.subscribed.insert(characteristic.uuid);
+        Ok(())
+    }
+
+    async fn unsubscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()> {
+        self.state.lock().unwrap().subscribed.remove(&characteristic.uuid);
+        Ok(())
+    }
+
+    async fn notifications(&self) -> btleplug::Result<NotificationStream> {
+        let notif_rx = self.notif_tx.subscribe();
+        Ok(Box::pin(stream::unfold(notif_rx, |mut notif_rx| async move {
+            loop {
+                match notif_rx.recv().await {
+                    Ok(value) => return Some((value, notif_rx)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None
+                }
+            }
+        })))
+    }
+
+    async fn connected(&self) -> btleplug::Result<bool> {
+        Ok(self.state.lock().unwrap().connected)
+    }
+
+    async fn reconnect(&self) -> btleplug::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.powered_on {
+            return Err(btleplug::Error::DeviceNotFound);
+        }
+
+        state.connected = true;
+        Ok(())
+    }
+
+    async fn mtu(&self) -> Option<usize> {
+        self.state.lock().unwrap().mtu
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time;
+use ls02::common::*;
+use super::SimWatch;
+
+// Things the watch does on its own (user interaction, sensors...)
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SimEvent {
+    Pulse(DevicePulseType),
+    SilentMode(SilentModeStatus),
+    SportStart(SportKind),
+    SportFinish(SportKind),
+    HeartRateMenuEnter,
+    HeartRateMenuMeasurement(u8),
+    HeartRateMenuLeave(u8),
+    HeartRatePeriodic(u8),
+    Steps(u8),
+    Battery(u8)
+}
+
+impl SimWatch {
+    pub fn emit(&self, event: SimEvent) {
+        use ls02::general::res::*;
+        use ls02::data2::res::*;
+
+        match event {
+            SimEvent::Pulse(pulse_type) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &DevicePulseResponse {
+                id: CommandId::DevicePulse,
+                pulse_type
+            }),
+            SimEvent::SilentMode(mode) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &SilentModeChangeResponse {
+                id: CommandId::SilentMode,
+                sub_id: SilentModeCommandSubId::ModeChanged,
+                mode,
+                unk: [0; 0x11]
+            }),
+            SimEvent::SportStart(kind) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &SportStatusResponse {
+                id: CommandId::Sport,
+                sub_id: SportCommandSubId::Start,
+                kind,
+                unk: 0
+            }),
+            SimEvent::SportFinish(kind) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &SportStatusResponse {
+                id: CommandId::Sport,
+                sub_id: SportCommandSubId::Finish,
+                kind,
+                unk: 0
+            }),
+            SimEvent::HeartRateMenuEnter => self.notify_response(&ls02::CHAR_GENERAL_N_1, &HeartRateMenuChangeResponse {
+                id: CommandId::HeartRateMenuGeneral,
+                sub_id: HeartRateMenuCommandSubId::InMenu
+            }),
+            SimEvent::HeartRateMenuMeasurement(heart_rate) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &HeartRateMenuDataPeriodicResponse {
+                id: CommandId::HeartRateMenuGeneral,
+                sub_id: HeartRateMenuCommandSubId::InMenu,
+                unk_maybe_pad: 0,
+                heart_rate
+            }),
+            SimEvent::HeartRateMenuLeave(heart_rate) => self.notify_response(&ls02::CHAR_GENERAL_N_1, &HeartRateMenuDataLeavingResponse {
+                id: CommandId::HeartRateMenuGeneral,
+                sub_id: HeartRateMenuCommandSubId::LeavingMenu,
+                unk_maybe_pad: 0,
+                heart_rate
+            }),
+            SimEvent::HeartRatePeriodic(heart_rate) => {
+                let state = self.state();
+                self.notify_response(&ls02::CHAR_DATA2_N, &HeartRateDataPeriodicResponse {
+                    id: CommandId::HeartRate,
+                    sub_id: HeartRateCommandSubId::DataPeriodic,
+                    date: state.date,
+                    hour: state.hour,
+                    min: state.min,
+                    heart_rate
+                });
+            },
+            SimEvent::Steps(new_step_count) => {
+                let state = self.state();
+                self.notify_response(&ls02::CHAR_DATA2_N, &StepsResponse {
+                    id: CommandId::Steps,
+                    date: state.date,
+                    hour: state.hour,
+                    min: state.min,
+                    new_step_count,
+                    unk: [0; 10]
+                });
+            },
+            SimEvent::Battery(battery_percentage) => {
+                self.state.lock().unwrap().battery = battery_percentage;
+                self.notify_response(&ls02::CHAR_GENERAL_N_1, &BatteryResponse {
+                    id: CommandId::Battery,
+                    battery_percentage
+                });
+                self.notify(&ls02::CHAR_BATTERY_LEVEL, vec![battery_percentage]);
+            }
+        }
+    }
+
+    // Each event is emitted after waiting its delay (relative to the previous event)
+    pub fn play(&self, script: Vec<(Duration, SimEvent)>) -> JoinHandle<()> {
+        let sim = self.clone();
+        tokio::spawn(async move {
+            for (delay, event) in script {
+                time::sleep(delay).await;
+                sim.emit(event);
+            }
+        })
+    }
+}
@@ -0,0 +1,175 @@
+// The simulator must answer (and notify) exactly like a real watch does, at the raw frame level
+
+use std::time::Duration;
+use binrw::BinWrite;
+use btleplug::api::WriteType;
+use futures::FutureExt;
+use futures::stream::StreamExt;
+use ls02::{CommandRequest, Response, WatchTransport};
+use ls02::common::*;
+use ls02::transport::NotificationStream;
+use ls02_sim::{encode, SimConfig, SimWatch, DEFAULT_FIRMWARE};
+use ls02_sim::script::SimEvent;
+
+async fn subscribed(sim: &SimWatch) -> NotificationStream {
+    sim.subscribe(&ls02::CHAR_GENERAL_N_1).await.unwrap();
+    sim.subscribe(&ls02::CHAR_DATA2_N).await.unwrap();
+    sim.notifications().await.unwrap()
+}
+
+// Everything notified so far, decoded
+fn received(notifs: &mut NotificationStream) -> Vec<Response> {
+    let mut responses = Vec::new();
+    while let Some(Some(notif)) = notifs.next().now_or_never() {
+        responses.push(Response::from_notification(&notif));
+    }
+    responses
+}
+
+async fn send<R: CommandRequest>(sim: &SimWatch, req: &R) where for<'a> <R as BinWrite>::Args<'a>: Default {
+    sim.write(&R::CHAR, &req.build().unwrap(), R::WRITE_TYPE).await.unwrap();
+}
+
+#[tokio::test]
+async fn firmware_name() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+
+    send(&sim, &ls02::general::req::FirmwareRequest::new()).await;
+    match &received(&mut notifs)[..] {
+        [Response::Firmware(res)] => {
+            assert_eq!(&res.name, b"RH208DV000924");
+            assert_eq!(res.name, DEFAULT_FIRMWARE);
+        },
+        responses => panic!("Received {:?}", responses)
+    }
+}
+
+#[tokio::test]
+async fn settings_are_echoed() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+
+    let date = Date::new(2024, 5, 6);
+    send(&sim, &ls02::general::req::SetDateTimeRequest::new(date, 12, 34, 56)).await;
+    match &received(&mut notifs)[..] {
+        [Response::SetDateTime(res)] => assert_eq!((res.date, res.hour, res.min, res.sec), (date, 12, 34, 56)),
+        responses => panic!("Received {:?}", responses)
+    }
+    assert_eq!(sim.state().date, date);
+
+    let req = ls02::general::req::ConfigDisplayFormatsRequest::new(DistanceUnit::Imperial, TimeFormat::H12);
+    send(&sim, &req).await;
+    match &received(&mut notifs)[..] {
+        [Response::ConfigDisplayFormats(res)] => assert_eq!(encode(res), req.build().unwrap()),
+        responses => panic!("Received {:?}", responses)
+    }
+    assert_eq!((sim.state().distance_unit, sim.state().time_fmt), (DistanceUnit::Imperial, TimeFormat::H12));
+
+    send(&sim, &ls02::general::req::SetWeatherTodayRequest::new(WeatherType::Sunny, 20, 25, 15)).await;
+    match &received(&mut notifs)[..] {
+        [Response::SetWeather(res)] => assert_eq!(res.weather_date, WeatherDate::Today),
+        responses => panic!("Received {:?}", responses)
+    }
+    assert!(sim.state().weather_today.is_some());
+}
+
+#[tokio::test]
+async fn heart_rate_history_ends_with_end_response() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+
+    send(&sim, &ls02::data2::req::HeartRateDataRequest::new()).await;
+    let responses = received(&mut notifs);
+
+    assert!(matches!(responses.first(), Some(Response::HeartRateDataToday(_))));
+    assert!(matches!(responses.last(), Some(Response::HeartRateDataEnd(_))));
+    let entries = &responses[1..responses.len() - 1];
+    assert_eq!(entries.len(), ls02_sim::history::heart_rate_entries(&sim.state()).len());
+    assert!(entries.iter().all(|res| matches!(res, Response::HeartRateDataDayHourEntry(_))));
+}
+
+#[tokio::test]
+async fn recorded_steps_history_ends_with_end_response() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+    let entry_count = ls02_sim::history::steps_entries(&sim.state()).len();
+    assert!(entry_count > 0);
+
+    send(&sim, &ls02::general::req::RecordedStepsRequest::new()).await;
+    let responses = received(&mut notifs);
+    assert_eq!(responses.len(), entry_count + 1);
+    assert!(responses[..entry_count].iter().all(|res| matches!(res, Response::RecordedStepsEntryGeneral(_))));
+    assert!(matches!(responses.last(), Some(Response::RecordedStepsEndGeneral(_))));
+
+    send(&sim, &ls02::data2::req::RecordedStepsRequest::new()).await;
+    let responses = received(&mut notifs);
+    assert_eq!(responses.len(), entry_count + 1);
+    assert!(responses[..entry_count].iter().all(|res| matches!(res, Response::RecordedStepsEntryData2(_))));
+    assert!(matches!(responses.last(), Some(Response::RecordedStepsEndData2(_))));
+}
+
+#[tokio::test]
+async fn data2_recorded_steps_unanswered_if_disabled() {
+    let sim = SimWatch::new(SimConfig {
+        data2_recorded_steps: false,
+        ..Default::default()
+    });
+    let mut notifs = subscribed(&sim).await;
+
+    send(&sim, &ls02::data2::req::RecordedStepsRequest::new()).await;
+    assert!(received(&mut notifs).is_empty());
+}
+
+#[tokio::test]
+async fn unsubscribed_characteristics_get_nothing() {
+    let sim = SimWatch::default();
+    sim.subscribe(&ls02::CHAR_DATA2_N).await.unwrap();
+    let mut notifs = sim.notifications().await.unwrap();
+
+    sim.write(&ls02::CHAR_GENERAL_RW_1, &ls02::general::req::BatteryRequest::new().build().unwrap(), WriteType::WithResponse).await.unwrap();
+    assert!(received(&mut notifs).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn script_events() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+
+    sim.play(vec![
+        (Duration::from_millis(10), SimEvent::Pulse(DevicePulseType::Ring)),
+        (Duration::from_millis(10), SimEvent::SilentMode(SilentModeStatus::On)),
+        (Duration::from_millis(10), SimEvent::SportStart(SportKind::Jogging)),
+        (Duration::from_millis(10), SimEvent::HeartRateMenuEnter),
+        (Duration::from_millis(10), SimEvent::HeartRateMenuMeasurement(72)),
+        (Duration::from_millis(10), SimEvent::HeartRateMenuLeave(75))
+    ]).await.unwrap();
+
+    match &received(&mut notifs)[..] {
+        [Response::DevicePulse(pulse), Response::SilentModeChange(silent_mode), Response::SportStatus(sport), Response::HeartRateMenuChange(_), Response::HeartRateMenuDataPeriodic(measurement), Response::HeartRateMenuDataLeaving(leaving)] => {
+            assert_eq!(pulse.pulse_type, DevicePulseType::Ring);
+            assert_eq!(silent_mode.mode, SilentModeStatus::On);
+            assert_eq!((sport.sub_id, sport.kind), (SportCommandSubId::Start, SportKind::Jogging));
+            assert_eq!(measurement.heart_rate, 72);
+            assert_eq!(leaving.heart_rate, 75);
+        },
+        responses => panic!("Received {:?}", responses)
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn script_waits_for_each_delay() {
+    let sim = SimWatch::default();
+    let mut notifs = subscribed(&sim).await;
+
+    let script = sim.play(vec![
+        (Duration::from_secs(1), SimEvent::Pulse(DevicePulseType::Ring)),
+        (Duration::from_secs(1), SimEvent::Pulse(DevicePulseType::Ring))
+    ]);
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(received(&mut notifs).len(), 1);
+
+    script.await.unwrap();
+    assert_eq!(received(&mut notifs).len(), 1);
+}
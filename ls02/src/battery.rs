@@ -0,0 +1,44 @@
+use super::*;
+use std::pin::Pin;
+use futures::future;
+use futures::stream::{Stream, StreamExt};
+
+pub type BatteryStream = Pin<Box<dyn Stream<Item = u8> + Send>>;
+
+// Battery Level values are a single byte percentage
+pub fn parse_battery_level(raw: &[u8]) -> Option<u8> {
+    match raw {
+        [level] if *level <= 100 => Some(*level),
+        _ => None
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // Same as battery(), but through the standard Battery Service
+    pub async fn battery_level(&self) -> Result<u8> {
+        let raw = self.transport().read(&CHAR_BATTERY_LEVEL).await?;
+        parse_battery_level(&raw).ok_or(Error::Decode { char: CHAR_BATTERY_LEVEL.uuid, bytes: raw })
+    }
+
+    // Battery changes, as notified by either the standard Battery Level characteristic or the proprietary battery responses (the same level is never yielded twice in a row)
+    // If the transport can't subscribe to Battery Level notifications, only the proprietary ones are received
+    pub async fn battery_stream(&self) -> Result<BatteryStream> {
+        let notif_stream = self.transport().notifications().await?;
+        let _ = self.transport().subscribe(&CHAR_BATTERY_LEVEL).await;
+
+        let mut last_level = None;
+        Ok(Box::pin(notif_stream.filter_map(move |value_notif| {
+            let level = if value_notif.uuid == CHAR_BATTERY_LEVEL.uuid {
+                parse_battery_level(&value_notif.value)
+            }
+            else {
+                match Response::from_notification(&value_notif) {
+                    Response::Battery(battery_res) => Some(battery_res.battery_percentage),
+                    _ => None
+                }
+            };
+
+            future::ready(level.filter(|level| last_level.replace(*level) != Some(*level)))
+        })))
+    }
+}
@@ -1,11 +1,86 @@
 use std::fmt::{Debug, Formatter, Result};
+use std::cell::Cell;
 
 use super::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+// Enums of values the watch sends on its own, which newer firmwares may extend: unknown values are kept as such instead of failing to parse (unless parsing in strict mode)
+macro_rules! lenient_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($variant:ident = $value:expr),* }) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($variant,)*
+            Unknown(u8)
+        }
+
+        impl $name {
+            pub const fn is_unknown(&self) -> bool {
+                matches!(self, Self::Unknown(_))
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    _ => Self::Unknown(value)
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value
+                }
+            }
+        }
+
+        impl BinRead for $name {
+            type Args<'a> = ();
+
+            fn read_options<R: binrw::io::Read + binrw::io::Seek>(reader: &mut R, endian: binrw::Endian, args: Self::Args<'_>) -> binrw::BinResult<Self> {
+                let pos = reader.stream_position()?;
+                let value = Self::from(u8::read_options(reader, endian, args)?);
+                if value.is_unknown() && (parse_mode() == ParseMode::Strict) {
+                    return Err(binrw::Error::NoVariantMatch { pos });
+                }
+                Ok(value)
+            }
+        }
+
+        impl BinWrite for $name {
+            type Args<'a> = ();
+
+            fn write_options<W: binrw::io::Write + binrw::io::Seek>(&self, writer: &mut W, endian: binrw::Endian, args: Self::Args<'_>) -> binrw::BinResult<()> {
+                u8::from(*self).write_options(writer, endian, args)
+            }
+        }
+    };
+}
+
+thread_local! {
+    static PARSE_MODE: Cell<ParseMode> = const { Cell::new(ParseMode::Lenient) };
+}
+
+fn parse_mode() -> ParseMode {
+    PARSE_MODE.with(|mode| mode.get())
+}
+
+// binrw has no way to pass this down to every (nested) field, thus it's set for the (synchronous) duration of the parsing
+pub(crate) fn with_parse_mode<R>(mode: ParseMode, f: impl FnOnce() -> R) -> R {
+    let prev_mode = PARSE_MODE.with(|cur_mode| cur_mode.replace(mode));
+    let res = f();
+    PARSE_MODE.with(|cur_mode| cur_mode.set(prev_mode));
+    res
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum CommandId {
+    #[default]
     Invalid = 0x00,
     Config = 0x01,
     DateTime = 0x04,
@@ -30,13 +105,8 @@ pub enum CommandId {
     Sport = 0xFD
 }
 
-impl Default for CommandId {
-    fn default() -> Self {
-        CommandId::Invalid
-    }
-}
-
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum PairCommandSubId {
@@ -47,8 +117,10 @@ pub enum PairCommandSubId {
 pub type PairKey = [u8; 4];
 
 #[derive(Copy, Clone, PartialEq, Eq, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Date {
-    pub year_be: u16,
+    #[brw(big)]
+    pub year: u16,
     pub month: u8,
     pub day: u8
 }
@@ -56,14 +128,14 @@ pub struct Date {
 impl Date {
     pub const fn new(year: u16, month: u8, day: u8) -> Self {
         Self {
-            year_be: year.swap_bytes(),
+            year,
             month,
             day
         }
     }
 
     pub fn year(&self) -> u16 {
-        self.year_be.swap_bytes()
+        self.year
     }
 
     pub fn month(&self) -> u8 {
@@ -73,15 +145,29 @@ impl Date {
     pub fn day(&self) -> u8 {
         self.day
     }
+
+    pub fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month) && (1..=Self::days_in_month(self.year(), self.month)).contains(&self.day)
+    }
 }
 
 impl Debug for Date {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{:04}/{:02}/{:02}", self.year_be.swap_bytes(), self.month, self.day)
+        write!(f, "{:04}/{:02}/{:02}", self.year, self.month, self.day)
     }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum ResetCommandSubId {
@@ -89,18 +175,20 @@ pub enum ResetCommandSubId {
     ResetAndPowerOff = 0x01
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
-#[repr(u8)]
-#[brw(repr = u8)]
-pub enum DevicePulseType {
-    HangCall = 2,
-    MusicPauseResume = 7,
-    MusicNext = 8,
-    MusicPrevious = 9,
-    Ring = 10
+lenient_enum! {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum DevicePulseType {
+        HangCall = 2,
+        MusicPauseResume = 7,
+        MusicNext = 8,
+        MusicPrevious = 9,
+        Ring = 10
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum TimeFormat {
@@ -109,6 +197,7 @@ pub enum TimeFormat {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum DistanceUnit {
@@ -116,36 +205,38 @@ pub enum DistanceUnit {
     Imperial = 2
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
-#[repr(u8)]
-#[brw(repr = u8)]
-pub enum AlertType {
-    Call = 0,
-    QQ = 1,
-    WeChat = 2,
-    Message = 4,
-    Facebook = 5,
-    Twitter = 6,
-    WhatsApp = 7,
-    Skype = 8,
-    Messenger = 9,
-    Hangouts = 10,
-    LINE = 11,
-    LinkedIn = 12,
-    Instagram = 13,
-    Viber = 14,
-    KakaoTalk = 15,
-    VK = 16,
-    Snapchat = 17,
-    GooglePlus = 18,
-    Email = 19,
-    Flickr = 20,
-    Tumblr = 21,
-    Pinterest = 22,
-    YouTube = 23
+lenient_enum! {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum AlertType {
+        Call = 0,
+        QQ = 1,
+        WeChat = 2,
+        Message = 4,
+        Facebook = 5,
+        Twitter = 6,
+        WhatsApp = 7,
+        Skype = 8,
+        Messenger = 9,
+        Hangouts = 10,
+        LINE = 11,
+        LinkedIn = 12,
+        Instagram = 13,
+        Viber = 14,
+        KakaoTalk = 15,
+        VK = 16,
+        Snapchat = 17,
+        GooglePlus = 18,
+        Email = 19,
+        Flickr = 20,
+        Tumblr = 21,
+        Pinterest = 22,
+        YouTube = 23
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum UserGender {
@@ -154,6 +245,7 @@ pub enum UserGender {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum UserLiftWristMode {
@@ -162,6 +254,7 @@ pub enum UserLiftWristMode {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum WeatherDate {
@@ -169,28 +262,29 @@ pub enum WeatherDate {
     FollowingDays = 2
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
-#[repr(u8)]
-#[brw(repr = u8)]
-pub enum WeatherType {
-    Sunny = 1,
-    SunnyCloudy = 2,
-    Cloudy = 3,
-    SunnyRainy = 4,
-    Stormy = 5,
-    Rainy = 6,
-    SlighlyRainy = 7,
-    VeryRainy = 8,
-    Snowy = 9,
-    S = 10,
-    Foggy = 11,
-    Windy = 12,
-    Night = 13,
-    CloudyNight = 14,
-    RainyNight = 15
+lenient_enum! {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum WeatherType {
+        Sunny = 1,
+        SunnyCloudy = 2,
+        SunnyRainy = 3,
+        Stormy = 4,
+        Rainy = 5,
+        SlighlyRainy = 6,
+        VeryRainy = 7,
+        Snowy = 8,
+        S = 9,
+        Foggy = 10,
+        Windy = 11,
+        Night = 12,
+        CloudyNight = 13,
+        RainyNight = 14
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum HeartRateCommandSubId {
@@ -203,6 +297,7 @@ pub enum HeartRateCommandSubId {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum HeartRateMenuCommandSubId {
@@ -213,21 +308,24 @@ pub enum HeartRateMenuCommandSubId {
 pub const INVALID_HEART_RATE: u8 = 255;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum SilentModeCommandSubId {
     ModeChanged = 2
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
-#[repr(u8)]
-#[brw(repr = u8)]
-pub enum SilentModeStatus {
-    Off = 0x0,
-    On = 0x8,
+lenient_enum! {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum SilentModeStatus {
+        Off = 0x0,
+        On = 0x8
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum SportCommandSubId {
@@ -235,43 +333,49 @@ pub enum SportCommandSubId {
     Start = 0x11
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
-#[repr(u8)]
-#[brw(repr = u8)]
-pub enum SportKind {
-    Jogging = 1,
-    Biking = 2,
-    Climbing = 8,
-    FastRunning = 9,
-    Basketball = 10,
-    Football = 11,
-    Spinning = 18,
-    Yoga = 19,
-    IndoorRunning = 21,
-    Gimnastics = 22,
-    Rowing = 23,
-    IntegratedTraining = 25
+lenient_enum! {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum SportKind {
+        Jogging = 1,
+        Biking = 2,
+        Climbing = 8,
+        FastRunning = 9,
+        Basketball = 10,
+        Football = 11,
+        Spinning = 18,
+        Yoga = 19,
+        IndoorRunning = 21,
+        Gimnastics = 22,
+        Rowing = 23,
+        IntegratedTraining = 25
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StepsEntry {
     pub date: Date,
     pub hour: u8,
-    pub new_all_step_count_be: u16,
+    #[brw(big)]
+    pub new_all_step_count: u16,
     pub unk_1: u8,
     pub last_new_run_step_min: u8,
     pub unk_2: u8,
-    pub new_run_step_count_be: u16,
+    #[brw(big)]
+    pub new_run_step_count: u16,
     pub unk_3: u8,
     pub last_new_walk_step_min: u8,
     pub unk_4: u8,
-    pub new_walk_step_count_be: u16
+    #[brw(big)]
+    pub new_walk_step_count: u16
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[brw(repr = u8)]
 pub enum RecordedStepsCommandSubId {
     Request = 0x03,
     End = 0xFD
-}
\ No newline at end of file
+}
@@ -1,11 +1,13 @@
 use super::*;
 use super::common::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
-
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataRequest {
+    #[br(assert(id == CommandId::HeartRate))]
     #[bw(assert(*id == CommandId::HeartRate))]
     pub id: CommandId,
+    #[br(assert(sub_id == HeartRateCommandSubId::DataRequest))]
     #[bw(assert(*sub_id == HeartRateCommandSubId::DataRequest))]
     pub sub_id: HeartRateCommandSubId
 }
@@ -19,16 +21,24 @@ impl HeartRateDataRequest {
     }
 }
 
+impl Default for HeartRateDataRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for HeartRateDataRequest {
     const WRITE_TYPE: WriteType = WriteType::WithoutResponse;
     const CHAR: Characteristic = super::CHAR_DATA2_RW;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
-
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateEnableRequest {
+    #[br(assert(id == CommandId::HeartRate))]
     #[bw(assert(*id == CommandId::HeartRate))]
     pub id: CommandId,
+    #[br(assert(sub_id == HeartRateCommandSubId::Enable))]
     #[bw(assert(*sub_id == HeartRateCommandSubId::Enable))]
     pub sub_id: HeartRateCommandSubId
 }
@@ -42,16 +52,24 @@ impl HeartRateEnableRequest {
     }
 }
 
+impl Default for HeartRateEnableRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for HeartRateEnableRequest {
     const WRITE_TYPE: WriteType = WriteType::WithoutResponse;
     const CHAR: Characteristic = super::CHAR_DATA2_RW;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
-
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDisableRequest {
+    #[br(assert(id == CommandId::HeartRate))]
     #[bw(assert(*id == CommandId::HeartRate))]
     pub id: CommandId,
+    #[br(assert(sub_id == HeartRateCommandSubId::Disable))]
     #[bw(assert(*sub_id == HeartRateCommandSubId::Disable))]
     pub sub_id: HeartRateCommandSubId
 }
@@ -65,18 +83,27 @@ impl HeartRateDisableRequest {
     }
 }
 
+impl Default for HeartRateDisableRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for HeartRateDisableRequest {
     const WRITE_TYPE: WriteType = WriteType::WithoutResponse;
     const CHAR: Characteristic = super::CHAR_DATA2_RW;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
-
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsRequest {
+    #[br(assert(id == CommandId::RecordedStepsData2))]
     #[bw(assert(*id == CommandId::RecordedStepsData2))]
     pub id: CommandId,
+    #[br(assert(sub_id == RecordedStepsCommandSubId::Request))]
     #[bw(assert(*sub_id == RecordedStepsCommandSubId::Request))]
     pub sub_id: RecordedStepsCommandSubId,
+    #[br(assert(unk == 1))]
     #[bw(assert(*unk == 1))]
     pub unk: u8
 }
@@ -91,6 +118,12 @@ impl RecordedStepsRequest {
     }
 }
 
+impl Default for RecordedStepsRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for RecordedStepsRequest {
     const WRITE_TYPE: WriteType = WriteType::WithoutResponse;
     const CHAR: Characteristic = super::CHAR_DATA2_RW;
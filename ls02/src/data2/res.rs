@@ -1,7 +1,8 @@
 use super::*;
 use super::common::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataTodayResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -19,7 +20,8 @@ impl CommandResponse for HeartRateDataTodayResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataEndResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -32,7 +34,8 @@ impl CommandResponse for HeartRateDataEndResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataDayHourEntryResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -45,7 +48,8 @@ impl CommandResponse for HeartRateDataDayHourEntryResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataPeriodicResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -61,7 +65,8 @@ impl CommandResponse for HeartRateDataPeriodicResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateEnableResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -73,7 +78,8 @@ impl CommandResponse for HeartRateEnableResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDisableResponse {
     #[br(assert(id == CommandId::HeartRate))]
     pub id: CommandId,
@@ -85,7 +91,8 @@ impl CommandResponse for HeartRateDisableResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StepsResponse {
     #[br(assert(id == CommandId::Steps))]
     pub id: CommandId,
@@ -100,7 +107,8 @@ impl CommandResponse for StepsResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMenuPeriodicResponse {
     #[br(assert(id == CommandId::HeartRateMenuData))]
     pub id: CommandId,
@@ -114,7 +122,8 @@ impl CommandResponse for HeartRateMenuPeriodicResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMenuMoveDownResponse {
     #[br(assert(id == CommandId::HeartRateMenuData))]
     pub id: CommandId,
@@ -126,7 +135,8 @@ impl CommandResponse for HeartRateMenuMoveDownResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataTodayUnk2Response {
     #[br(assert(id == CommandId::HeartRateUnk2))]
     pub id: CommandId,
@@ -144,7 +154,8 @@ impl CommandResponse for HeartRateDataTodayUnk2Response {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StepsUnk2Response {
     #[br(assert(id == CommandId::StepsUnk2))]
     pub id: CommandId,
@@ -155,7 +166,8 @@ impl CommandResponse for StepsUnk2Response {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateDataPeriodicUnk2Response {
     #[br(assert(id == CommandId::HeartRateUnk2))]
     pub id: CommandId,
@@ -171,7 +183,8 @@ impl CommandResponse for HeartRateDataPeriodicUnk2Response {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DevicePulseUnk2Response {
     #[br(assert(id == CommandId::DevicePulseUnk2))]
     pub id: CommandId,
@@ -182,7 +195,8 @@ impl CommandResponse for DevicePulseUnk2Response {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsEntryResponse {
     #[br(assert(id == CommandId::RecordedStepsData2))]
     pub id: CommandId,
@@ -193,7 +207,8 @@ impl CommandResponse for RecordedStepsEntryResponse {
     const CHAR: Characteristic = super::CHAR_DATA2_N;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsEndResponse {
     #[br(assert(id == CommandId::RecordedStepsData2))]
     pub id: CommandId,
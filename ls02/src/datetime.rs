@@ -0,0 +1,237 @@
+use super::*;
+use super::common::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Anything carrying the date/time something happened at (as reported by the watch, in its local time)
+pub trait Timestamp {
+    // (date, hour, min, sec)
+    fn timestamp(&self) -> (Date, u8, u8, u8);
+
+    fn date(&self) -> Date {
+        self.timestamp().0
+    }
+
+    fn minute_of_day(&self) -> u32 {
+        let (_, hour, min, _) = self.timestamp();
+        hour as u32 * 60 + min as u32
+    }
+
+    #[cfg(feature = "chrono")]
+    fn naive_date_time(&self) -> Result<chrono::NaiveDateTime> {
+        let (date, hour, min, sec) = self.timestamp();
+        let time = chrono::NaiveTime::from_hms_opt(hour as u32, min as u32, sec as u32).ok_or_else(|| invalid_time(hour, min, sec))?;
+        Ok(chrono::NaiveDate::try_from(date)?.and_time(time))
+    }
+
+    #[cfg(feature = "time")]
+    fn primitive_date_time(&self) -> Result<::time::PrimitiveDateTime> {
+        let (date, hour, min, sec) = self.timestamp();
+        let time = ::time::Time::from_hms(hour, min, sec).map_err(|_| invalid_time(hour, min, sec))?;
+        Ok(::time::PrimitiveDateTime::new(::time::Date::try_from(date)?, time))
+    }
+}
+
+fn invalid_date(date: Date) -> Error {
+    Error::InvalidArgument(format!("Invalid date: {:?}", date))
+}
+
+pub(crate) fn invalid_time(hour: u8, min: u8, sec: u8) -> Error {
+    Error::InvalidArgument(format!("Invalid time: {:02}:{:02}:{:02}", hour, min, sec))
+}
+
+fn checked_year(year: i32) -> Result<u16> {
+    u16::try_from(year).map_err(|_| Error::InvalidArgument(format!("Year {} can't be sent to the watch", year)))
+}
+
+impl Date {
+    pub fn checked(self) -> Result<Self> {
+        match self.is_valid() {
+            true => Ok(self),
+            false => Err(invalid_date(self))
+        }
+    }
+
+    pub fn try_from_ymd(year: i32, month: u8, day: u8) -> Result<Self> {
+        Self::new(checked_year(year)?, month, day).checked()
+    }
+
+    // Days since 1970/01/01 (see http://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+    pub fn try_from_unix_days(days: i64) -> Result<Self> {
+        let shifted_days = days + 719468;
+        let era = shifted_days.div_euclid(146097);
+        let day_of_era = shifted_days.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        Self::try_from_ymd(year.clamp(i32::MIN as i64, i32::MAX as i64) as i32, month, day)
+    }
+}
+
+impl general::req::SetDateTimeRequest {
+    // The watch has no notion of time zones, thus the offset (seconds east of UTC) of the local time to set is needed
+    pub fn from_system_time(time: SystemTime, utc_offset_secs: i32) -> Result<Self> {
+        let unix_secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64)
+        } + utc_offset_secs as i64;
+
+        let date = Date::try_from_unix_days(unix_secs.div_euclid(SECS_PER_DAY))?;
+        let sec_of_day = unix_secs.rem_euclid(SECS_PER_DAY);
+        Self::try_new(date, (sec_of_day / 3600) as u8, (sec_of_day / 60 % 60) as u8, (sec_of_day % 60) as u8)
+    }
+
+    pub fn now(utc_offset_secs: i32) -> Result<Self> {
+        Self::from_system_time(SystemTime::now(), utc_offset_secs)
+    }
+}
+
+impl Timestamp for general::res::SetDateTimeResponse {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, self.sec)
+    }
+}
+
+impl Timestamp for general::req::SetDateTimeRequest {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, self.sec)
+    }
+}
+
+impl Timestamp for data2::res::HeartRateDataTodayResponse {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+impl Timestamp for data2::res::HeartRateDataTodayUnk2Response {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+impl Timestamp for data2::res::HeartRateDataPeriodicResponse {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+impl Timestamp for data2::res::HeartRateDataPeriodicUnk2Response {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+impl Timestamp for data2::res::HeartRateDataDayHourEntryResponse {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, 0, 0)
+    }
+}
+
+impl Timestamp for data2::res::StepsResponse {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+// Entries cover a whole hour, thus they are timestamped at its start
+impl Timestamp for StepsEntry {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, 0, 0)
+    }
+}
+
+impl Timestamp for history::HeartRateSummary {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, self.min, 0)
+    }
+}
+
+impl Timestamp for history::HeartRateHistoryEntry {
+    fn timestamp(&self) -> (Date, u8, u8, u8) {
+        (self.date, self.hour, 0, 0)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::*;
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+
+    impl TryFrom<Date> for NaiveDate {
+        type Error = Error;
+
+        fn try_from(date: Date) -> Result<Self> {
+            NaiveDate::from_ymd_opt(date.year() as i32, date.month() as u32, date.day() as u32).ok_or_else(|| invalid_date(date))
+        }
+    }
+
+    impl TryFrom<NaiveDate> for Date {
+        type Error = Error;
+
+        fn try_from(date: NaiveDate) -> Result<Self> {
+            Date::try_from_ymd(date.year(), date.month() as u8, date.day() as u8)
+        }
+    }
+
+    impl TryFrom<general::res::SetDateTimeResponse> for NaiveDateTime {
+        type Error = Error;
+
+        fn try_from(res: general::res::SetDateTimeResponse) -> Result<Self> {
+            res.naive_date_time()
+        }
+    }
+
+    // Leap seconds (or sub-second precision) can't be sent to the watch, thus seconds are clamped
+    impl TryFrom<NaiveDateTime> for general::req::SetDateTimeRequest {
+        type Error = Error;
+
+        fn try_from(date_time: NaiveDateTime) -> Result<Self> {
+            Self::try_new(Date::try_from(date_time.date())?, date_time.hour() as u8, date_time.minute() as u8, date_time.second().min(59) as u8)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use super::*;
+    use ::time::{Month, PrimitiveDateTime};
+
+    impl TryFrom<Date> for ::time::Date {
+        type Error = Error;
+
+        fn try_from(date: Date) -> Result<Self> {
+            let month = Month::try_from(date.month()).map_err(|_| invalid_date(date))?;
+            ::time::Date::from_calendar_date(date.year() as i32, month, date.day()).map_err(|_| invalid_date(date))
+        }
+    }
+
+    impl TryFrom<::time::Date> for Date {
+        type Error = Error;
+
+        fn try_from(date: ::time::Date) -> Result<Self> {
+            Date::try_from_ymd(date.year(), date.month() as u8, date.day())
+        }
+    }
+
+    impl TryFrom<general::res::SetDateTimeResponse> for PrimitiveDateTime {
+        type Error = Error;
+
+        fn try_from(res: general::res::SetDateTimeResponse) -> Result<Self> {
+            res.primitive_date_time()
+        }
+    }
+
+    impl TryFrom<PrimitiveDateTime> for general::req::SetDateTimeRequest {
+        type Error = Error;
+
+        fn try_from(date_time: PrimitiveDateTime) -> Result<Self> {
+            Self::try_new(Date::try_from(date_time.date())?, date_time.hour(), date_time.minute(), date_time.second())
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use super::*;
+use std::pin::Pin;
+use std::time::Duration;
+use btleplug::api::{BDAddr, Central, CentralEvent, Manager as _, Peripheral as _, ScanFilter};
+use btleplug::platform::{Adapter, Manager};
+use futures::stream::{self, Stream, StreamExt};
+use tokio::time;
+
+// Everything the rest of the crate needs to be present after service discovery
+pub const REQUIRED_CHARACTERISTICS: &[Characteristic] = &[
+    CHAR_GENERAL_RW_1,
+    CHAR_GENERAL_N_1,
+    CHAR_DATA2_RW,
+    CHAR_DATA2_N
+];
+
+#[derive(Clone, Debug)]
+pub struct WatchCandidate {
+    pub peripheral: Peripheral,
+    pub address: BDAddr,
+    pub name: Option<String>,
+    pub rssi: Option<i16>,
+    pub is_connected: bool
+}
+
+pub type DiscoverStream = Pin<Box<dyn Stream<Item = WatchCandidate> + Send>>;
+
+impl WatchCandidate {
+    // None if the peripheral doesn't look like a LS02 (neither advertises its services nor has its name)
+    pub async fn from_peripheral(peripheral: Peripheral) -> Result<Option<Self>> {
+        let properties = match peripheral.properties().await? {
+            Some(properties) => properties,
+            None => return Ok(None)
+        };
+
+        let has_services = properties.services.contains(&SERVICE_GENERAL) || properties.services.contains(&SERVICE_DATA2);
+        let has_name = properties.local_name.as_deref() == Some(DEVICE_NAME);
+        if !has_services && !has_name {
+            return Ok(None);
+        }
+
+        let is_connected = peripheral.is_connected().await?;
+        Ok(Some(Self {
+            peripheral,
+            address: properties.address,
+            name: properties.local_name,
+            rssi: properties.rssi,
+            is_connected
+        }))
+    }
+
+    // Connects (unless already connected), discovers services and checks that the expected characteristics are there
+    pub async fn connect(&self) -> Result<Watch<Peripheral>> {
+        if !self.peripheral.is_connected().await? {
+            self.peripheral.connect().await?;
+        }
+        self.peripheral.discover_services().await?;
+
+        let characteristics = self.peripheral.characteristics();
+        for required_char in REQUIRED_CHARACTERISTICS {
+            if !characteristics.iter().any(|characteristic| characteristic.uuid == required_char.uuid) {
+                return Err(Error::MissingCharacteristic(required_char.uuid));
+            }
+        }
+
+        Watch::new(self.peripheral.clone()).await
+    }
+}
+
+pub async fn adapters() -> Result<Vec<Adapter>> {
+    let manager = Manager::new().await?;
+    Ok(manager.adapters().await?)
+}
+
+// With no name the first adapter is chosen, otherwise the first one whose info (as reported by the platform, usually containing its name/address) contains it
+pub async fn select_adapter(name: Option<&str>) -> Result<Adapter> {
+    for adapter in adapters().await? {
+        match name {
+            Some(name) if !adapter.adapter_info().await?.contains(name) => continue,
+            _ => return Ok(adapter)
+        }
+    }
+
+    Err(Error::AdapterNotFound)
+}
+
+// Starts scanning and yields every LS02-looking device: first the ones the adapter already knows about (for instance, already connected ones), then newly discovered ones
+// A device may be yielded several times, whenever it gets updated (RSSI changes, etc.)
+pub async fn discover(adapter: &Adapter) -> Result<DiscoverStream> {
+    let events = adapter.events().await?;
+
+    let scan_filter = ScanFilter {
+        services: vec![SERVICE_GENERAL, SERVICE_DATA2]
+    };
+    adapter.start_scan(scan_filter).await?;
+
+    let mut known_candidates = Vec::new();
+    for peripheral in adapter.peripherals().await? {
+        if let Some(candidate) = WatchCandidate::from_peripheral(peripheral).await? {
+            known_candidates.push(candidate);
+        }
+    }
+
+    let adapter = adapter.clone();
+    let new_candidates = events.filter_map(move |event| {
+        let adapter = adapter.clone();
+        async move {
+            match event {
+                CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id) => {
+                    let peripheral = adapter.peripheral(&id).await.ok()?;
+                    WatchCandidate::from_peripheral(peripheral).await.ok().flatten()
+                },
+                _ => None
+            }
+        }
+    });
+
+    Ok(Box::pin(stream::iter(known_candidates).chain(new_candidates)))
+}
+
+// Scans until the first LS02 (or the one with the given address) is found, then stops scanning
+pub async fn find(adapter: &Adapter, address: Option<BDAddr>, timeout: Duration) -> Result<WatchCandidate> {
+    let mut candidates = discover(adapter).await?;
+
+    let find_candidate = async {
+        while let Some(candidate) = candidates.next().await {
+            if address.is_none() || (address == Some(candidate.address)) {
+                return Some(candidate);
+            }
+        }
+        None
+    };
+    let res = time::timeout(timeout, find_candidate).await;
+
+    adapter.stop_scan().await?;
+    match res {
+        Ok(Some(candidate)) => Ok(candidate),
+        _ => Err(Error::DeviceNotFound)
+    }
+}
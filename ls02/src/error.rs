@@ -0,0 +1,85 @@
+use super::*;
+use std::fmt;
+use std::time::Duration;
+use common::PairKey;
+
+#[derive(Debug)]
+pub enum Error {
+    // The underlying BLE (or simulated, etc.) transport failed
+    Transport(btleplug::Error),
+    // No (matching) Bluetooth adapter is present
+    AdapterNotFound,
+    // No (matching) watch was found while scanning
+    DeviceNotFound,
+    // The connected device lacks a characteristic the watch is expected to have
+    MissingCharacteristic(Uuid),
+    // No (matching) response was received in time
+    Timeout(Duration),
+    // The notification stream ended while waiting for a response, typically since the watch disconnected
+    NotificationStreamClosed,
+    // The request could not be serialized (for instance, a #[bw(assert(..))] failed)
+    Encode(binrw::Error),
+    // The received data is not a valid response of the expected kind
+    Decode { char: Uuid, bytes: Vec<u8> },
+    // The received data is a valid response of the expected kind, but followed by extra bytes
+    TrailingBytes { char: Uuid, bytes: Vec<u8>, parsed_len: usize },
+    // The request (or its payload) is bigger than what the watch accepts
+    PayloadTooLarge { len: usize, max: usize },
+    // A value that can't be sent to the watch as-is
+    InvalidArgument(String),
+    // The response was received through a different characteristic than expected
+    UnexpectedCharacteristic { expected: Uuid, got: Uuid },
+    // The watch responded with something other than expected (including echoed values not matching the request)
+    UnexpectedResponse(Response),
+    // The watch reports a different key than expected: after pairing (the pairing dialog was rejected or not answered in time) or when confirming a reset
+    PairKeyMismatch { expected: PairKey, current: PairKey },
+    // The pair key store could not be read or written
+    KeyStore(std::io::Error)
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(err) => write!(f, "Transport error: {}", err),
+            Error::AdapterNotFound => write!(f, "No Bluetooth adapter found"),
+            Error::DeviceNotFound => write!(f, "No watch found"),
+            Error::MissingCharacteristic(char) => write!(f, "Device has no {} characteristic", char),
+            Error::Timeout(timeout) => write!(f, "No response received after {:?}", timeout),
+            Error::NotificationStreamClosed => write!(f, "Notification stream closed before receiving a response"),
+            Error::Encode(err) => write!(f, "Unable to encode request: {}", err),
+            Error::Decode { char, bytes } => write!(f, "Unable to decode response from {}: {:?}", char, bytes),
+            Error::TrailingBytes { char, bytes, parsed_len } => write!(f, "Response from {} has {} trailing bytes: {:?}", char, bytes.len() - parsed_len, bytes),
+            Error::PayloadTooLarge { len, max } => write!(f, "Request is too large ({} bytes, max {})", len, max),
+            Error::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            Error::UnexpectedCharacteristic { expected, got } => write!(f, "Expected response from {}, got it from {}", expected, got),
+            Error::UnexpectedResponse(res) => write!(f, "Unexpected response: {:?}", res),
+            Error::PairKeyMismatch { expected, current } => write!(f, "Watch did not pair with key {:?}, its current key is {:?}", expected, current),
+            Error::KeyStore(err) => write!(f, "Pair key store error: {}", err)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(err) => Some(err),
+            Error::Encode(err) => Some(err),
+            Error::KeyStore(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<btleplug::Error> for Error {
+    fn from(err: btleplug::Error) -> Self {
+        Error::Transport(err)
+    }
+}
+
+impl From<binrw::Error> for Error {
+    fn from(err: binrw::Error) -> Self {
+        Error::Encode(err)
+    }
+}
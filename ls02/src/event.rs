@@ -0,0 +1,79 @@
+use super::*;
+use super::common::*;
+use std::pin::Pin;
+use futures::stream::{Stream, StreamExt};
+
+// Things the watch notifies on its own, regardless of the command ID / characteristic used to notify them
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WatchEvent {
+    // Hang call, music controls, ring...
+    Pulse(DevicePulseType),
+    SilentModeChanged(SilentModeStatus),
+    SportStarted(SportKind),
+    SportFinished(SportKind),
+    BatteryChanged(u8),
+    HeartRateMenuEntered,
+    // Sent about every second while in the heart rate menu
+    HeartRateMenuMeasurement(u8),
+    HeartRateMenuLeft(u8),
+    // Moving down in the heart rate menu, to the daily graph
+    HeartRateMenuGraphShown,
+    HeartRateSummary { date: Date, hour: u8, min: u8, max_heart_rate: u8, min_heart_rate: u8, avg_heart_rate: u8 },
+    // Sent every 10min
+    HeartRate { date: Date, hour: u8, min: u8, heart_rate: u8 },
+    NewSteps { date: Date, hour: u8, min: u8, new_step_count: u8 },
+    StepsRecorded(StepsEntry)
+}
+
+pub type EventStream = Pin<Box<dyn Stream<Item = WatchEvent> + Send>>;
+
+impl WatchEvent {
+    pub fn from_response(res: &Response) -> Option<Self> {
+        let event = match *res {
+            Response::DevicePulse(general::res::DevicePulseResponse { pulse_type, .. }) |
+            Response::DevicePulseUnk2(data2::res::DevicePulseUnk2Response { pulse_type, .. }) => WatchEvent::Pulse(pulse_type),
+
+            Response::SilentModeChange(silent_mode_res) => WatchEvent::SilentModeChanged(silent_mode_res.mode),
+
+            Response::SportStatus(sport_res) => match sport_res.sub_id {
+                SportCommandSubId::Start => WatchEvent::SportStarted(sport_res.kind),
+                SportCommandSubId::Finish => WatchEvent::SportFinished(sport_res.kind)
+            },
+
+            Response::Battery(battery_res) => WatchEvent::BatteryChanged(battery_res.battery_percentage),
+
+            Response::HeartRateMenuChange(_) => WatchEvent::HeartRateMenuEntered,
+
+            Response::HeartRateMenuDataPeriodic(general::res::HeartRateMenuDataPeriodicResponse { heart_rate, .. }) |
+            Response::HeartRateMenuPeriodic(data2::res::HeartRateMenuPeriodicResponse { heart_rate, .. }) => WatchEvent::HeartRateMenuMeasurement(heart_rate),
+
+            Response::HeartRateMenuDataLeaving(leaving_res) => WatchEvent::HeartRateMenuLeft(leaving_res.heart_rate),
+
+            Response::HeartRateMenuMoveDown(_) => WatchEvent::HeartRateMenuGraphShown,
+
+            Response::HeartRateDataToday(data2::res::HeartRateDataTodayResponse { date, hour, min, max_heart_rate, min_heart_rate, avg_heart_rate, .. }) |
+            Response::HeartRateDataTodayUnk2(data2::res::HeartRateDataTodayUnk2Response { date, hour, min, max_heart_rate, min_heart_rate, avg_heart_rate, .. }) => WatchEvent::HeartRateSummary { date, hour, min, max_heart_rate, min_heart_rate, avg_heart_rate },
+
+            Response::HeartRateDataPeriodic(data2::res::HeartRateDataPeriodicResponse { date, hour, min, heart_rate, .. }) |
+            Response::HeartRateDataPeriodicUnk2(data2::res::HeartRateDataPeriodicUnk2Response { date, hour, min, heart_rate, .. }) => WatchEvent::HeartRate { date, hour, min, heart_rate },
+
+            Response::Steps(steps_res) => WatchEvent::NewSteps { date: steps_res.date, hour: steps_res.hour, min: steps_res.min, new_step_count: steps_res.new_step_count },
+
+            Response::StepsUnk2(steps_res) => WatchEvent::StepsRecorded(steps_res.entry),
+
+            _ => return None
+        };
+        Some(event)
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // Notifications not carrying an event (history entries, echoes, unknown ones...) are skipped
+    pub async fn events(&self) -> Result<EventStream> {
+        let notif_stream = self.transport().notifications().await?;
+        Ok(Box::pin(notif_stream.filter_map(|value_notif| async move {
+            WatchEvent::from_response(&Response::from_notification(&value_notif))
+        })))
+    }
+}
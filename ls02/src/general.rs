@@ -1,4 +1,10 @@
 use super::*;
 
 pub mod req;
-pub mod res;
\ No newline at end of file
+pub mod res;
+
+pub mod alert;
+
+pub mod user_info;
+
+pub mod forecast;
\ No newline at end of file
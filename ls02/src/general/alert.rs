@@ -0,0 +1,147 @@
+use super::*;
+use super::common::*;
+use super::req::AlertRequest;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TruncationPolicy {
+    // Overlong messages are rejected
+    Error,
+    Truncate,
+    // Truncated messages end with an ellipsis
+    Ellipsis
+}
+
+// Cut by whole characters, so that surrogate pairs are never split
+fn truncate_utf16(text: &str, max_len: usize) -> String {
+    let mut truncated = String::new();
+    let mut truncated_len = 0;
+    for ch in text.chars() {
+        if truncated_len + ch.len_utf16() > max_len {
+            break;
+        }
+        truncated.push(ch);
+        truncated_len += ch.len_utf16();
+    }
+    truncated
+}
+
+// Builds the text of an alert (and the request sending it), making sure it fits in what the watch accepts
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlertMessage {
+    pub alert_type: AlertType,
+    pub title: Option<String>,
+    pub body: String,
+    // In UTF-16 units, at most AlertRequest::MAX_TEXT_LEN
+    pub max_len: usize,
+    pub truncation: TruncationPolicy,
+    pub transliterate: bool
+}
+
+impl AlertMessage {
+    pub const TITLE_SEPARATOR: &'static str = "\n";
+    pub const ELLIPSIS: char = '…';
+    pub const ELLIPSIS_TRANSLITERATED: &'static str = "...";
+
+    pub fn new(alert_type: AlertType, body: &str) -> Self {
+        Self {
+            alert_type,
+            title: None,
+            body: body.to_string(),
+            max_len: AlertRequest::MAX_TEXT_LEN,
+            truncation: TruncationPolicy::Error,
+            transliterate: false
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    pub fn with_truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    pub fn with_transliteration(mut self, transliterate: bool) -> Self {
+        self.transliterate = transliterate;
+        self
+    }
+
+    // The watch font only covers (part of) the BMP: typographic characters are replaced with ASCII equivalents, and characters outside the BMP (emojis, etc.) with '?'
+    pub fn transliterate_char(ch: char) -> Option<&'static str> {
+        match ch {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{2032}' => Some("'"),
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{2033}' | '\u{00AB}' | '\u{00BB}' => Some("\""),
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => Some("-"),
+            '\u{2026}' => Some("..."),
+            '\u{00A0}' | '\u{2002}'..='\u{200A}' | '\u{202F}' => Some(" "),
+            '\u{200B}'..='\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{FEFF}' => Some(""),
+            '\u{2022}' => Some("*"),
+            _ if (ch as u32) > 0xFFFF => Some("?"),
+            _ => None
+        }
+    }
+
+    // Full text as it will be sent (before any truncation)
+    pub fn full_text(&self) -> String {
+        let text = match self.title.as_ref() {
+            Some(title) => format!("{}{}{}", title, Self::TITLE_SEPARATOR, self.body),
+            None => self.body.clone()
+        };
+
+        if self.transliterate {
+            text.chars().fold(String::with_capacity(text.len()), |mut out, ch| {
+                match Self::transliterate_char(ch) {
+                    Some(replacement) => out.push_str(replacement),
+                    None => out.push(ch)
+                }
+                out
+            })
+        }
+        else {
+            text
+        }
+    }
+
+    pub fn text(&self) -> Result<String> {
+        if self.max_len > AlertRequest::MAX_TEXT_LEN {
+            return Err(Error::InvalidArgument(format!("Alert max length must be at most {} UTF-16 units, got {}", AlertRequest::MAX_TEXT_LEN, self.max_len)));
+        }
+
+        let full_text = self.full_text();
+        let full_len = full_text.encode_utf16().count();
+        if full_len <= self.max_len {
+            return Ok(full_text);
+        }
+
+        let ellipsis = match self.truncation {
+            TruncationPolicy::Error => return Err(Error::PayloadTooLarge { len: full_len * 2, max: self.max_len * 2 }),
+            TruncationPolicy::Truncate => String::new(),
+            TruncationPolicy::Ellipsis if self.transliterate => Self::ELLIPSIS_TRANSLITERATED.to_string(),
+            TruncationPolicy::Ellipsis => Self::ELLIPSIS.to_string()
+        };
+
+        // The ellipsis itself is shortened if not even it fits
+        let ellipsis = truncate_utf16(&ellipsis, self.max_len);
+        let mut text = truncate_utf16(&full_text, self.max_len - ellipsis.encode_utf16().count());
+        text.push_str(&ellipsis);
+        Ok(text)
+    }
+
+    pub fn build(&self) -> Result<AlertRequest> {
+        Ok(AlertRequest::new(self.alert_type, &self.text()?))
+    }
+
+    // Exact frames that would be written, for the given max write size (see Watch::max_write_size())
+    pub fn preview(&self, max_write_size: usize) -> Result<Vec<Vec<u8>>> {
+        self.build()?.fragment(max_write_size)
+    }
+}
@@ -0,0 +1,133 @@
+use std::ops::RangeInclusive;
+use super::*;
+use super::common::*;
+use super::req::{SetWeatherTodayRequest, SetWeatherFollowingDaysRequest};
+
+// The watch only has room for two digits (plus the sign), temperatures are always sent in Celsius
+pub const TEMPERATURE_RANGE: RangeInclusive<i8> = -99..=99;
+
+// Night weather variants are meant for 20:00 to 6:59
+pub const NIGHT_START_HOUR: u8 = 20;
+pub const NIGHT_END_HOUR: u8 = 7;
+
+pub fn is_night_hour(hour: u8) -> bool {
+    !(NIGHT_END_HOUR..NIGHT_START_HOUR).contains(&hour)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit
+}
+
+impl TemperatureUnit {
+    // Rounded and clamped to what the watch can display
+    pub fn to_celsius(self, temperature: f32) -> i8 {
+        let celsius = match self {
+            TemperatureUnit::Celsius => temperature,
+            TemperatureUnit::Fahrenheit => (temperature - 32.0) * 5.0 / 9.0
+        };
+        clamp_temperature(celsius.round().clamp(i8::MIN as f32, i8::MAX as f32) as i8)
+    }
+
+    pub fn from_celsius(self, celsius: i8) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius as f32,
+            TemperatureUnit::Fahrenheit => celsius as f32 * 9.0 / 5.0 + 32.0
+        }
+    }
+}
+
+pub fn clamp_temperature(celsius: i8) -> i8 {
+    celsius.clamp(*TEMPERATURE_RANGE.start(), *TEMPERATURE_RANGE.end())
+}
+
+impl WeatherType {
+    // WMO weather interpretation codes (as used by most weather APIs), None for unknown codes
+    // The watch only has night variants for clear, cloudy and rainy weather
+    pub fn from_wmo_code(code: u8, is_night: bool) -> Option<Self> {
+        let weather_type = match (code, is_night) {
+            (0, false) => WeatherType::Sunny,
+            (0, true) => WeatherType::Night,
+            // The watch has no plain cloudy type
+            (1..=3, false) => WeatherType::SunnyCloudy,
+            (1..=3, true) => WeatherType::CloudyNight,
+            (45 | 48, _) => WeatherType::Foggy,
+            (51..=57 | 61 | 80, false) => WeatherType::SlighlyRainy,
+            (62 | 63 | 66 | 67 | 81, false) => WeatherType::Rainy,
+            (64 | 65 | 82, false) => WeatherType::VeryRainy,
+            (51..=57 | 61..=67 | 80..=82, true) => WeatherType::RainyNight,
+            (71..=77 | 85 | 86, _) => WeatherType::Snowy,
+            (95..=99, _) => WeatherType::Stormy,
+            _ => return None
+        };
+        Some(weather_type)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DayWeather {
+    pub weather_type: WeatherType,
+    // Celsius
+    pub max_temperature: i8,
+    pub min_temperature: i8
+}
+
+impl DayWeather {
+    pub const fn new(weather_type: WeatherType, max_temperature: i8, min_temperature: i8) -> Self {
+        Self {
+            weather_type,
+            max_temperature,
+            min_temperature
+        }
+    }
+
+    pub fn from_unit(weather_type: WeatherType, max_temperature: f32, min_temperature: f32, unit: TemperatureUnit) -> Self {
+        Self::new(weather_type, unit.to_celsius(max_temperature), unit.to_celsius(min_temperature))
+    }
+}
+
+// Everything the watch shows about the weather: today (with the current temperature) and the following three days
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Forecast {
+    // Celsius
+    pub cur_temperature: i8,
+    pub today: DayWeather,
+    pub next: [DayWeather; 3]
+}
+
+impl Forecast {
+    pub const fn new(cur_temperature: i8, today: DayWeather, next: [DayWeather; 3]) -> Self {
+        Self {
+            cur_temperature,
+            today,
+            next
+        }
+    }
+
+    // Temperatures out of TEMPERATURE_RANGE are clamped
+    pub fn today_request(&self) -> SetWeatherTodayRequest {
+        SetWeatherTodayRequest::new(self.today.weather_type, clamp_temperature(self.cur_temperature), clamp_temperature(self.today.max_temperature), clamp_temperature(self.today.min_temperature))
+    }
+
+    pub fn following_days_request(&self) -> SetWeatherFollowingDaysRequest {
+        let [next_1, next_2, next_3] = self.next;
+        SetWeatherFollowingDaysRequest::new(
+            next_1.weather_type, clamp_temperature(next_1.max_temperature), clamp_temperature(next_1.min_temperature),
+            next_2.weather_type, clamp_temperature(next_2.max_temperature), clamp_temperature(next_2.min_temperature),
+            next_3.weather_type, clamp_temperature(next_3.max_temperature), clamp_temperature(next_3.min_temperature)
+        )
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // Both requests are confirmed by the watch
+    pub async fn set_forecast(&self, forecast: &Forecast) -> Result<()> {
+        self.set_weather_today_request(forecast.today_request()).await?;
+        self.set_weather_following_days(forecast.following_days_request()).await
+    }
+}
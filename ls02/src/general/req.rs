@@ -1,12 +1,15 @@
 use super::*;
 use super::common::*;
-use std::collections::VecDeque;
+use std::ops::Range;
 use std::mem::size_of;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PairRequest {
+    #[br(assert(id == CommandId::Pair))]
     #[bw(assert(*id == CommandId::Pair))]
     pub id: CommandId,
+    #[br(assert(sub_id == PairCommandSubId::Pair))]
     #[bw(assert(*sub_id == PairCommandSubId::Pair))]
     pub sub_id: PairCommandSubId,
     pub pair_key: PairKey
@@ -27,10 +30,13 @@ impl CommandRequest for PairRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PairKeyRequest {
+    #[br(assert(id == CommandId::Pair))]
     #[bw(assert(*id == CommandId::Pair))]
     pub id: CommandId,
+    #[br(assert(sub_id == PairCommandSubId::CurrentPairKey))]
     #[bw(assert(*sub_id == PairCommandSubId::CurrentPairKey))]
     pub sub_id: PairCommandSubId
 }
@@ -44,13 +50,21 @@ impl PairKeyRequest {
     }
 }
 
+impl Default for PairKeyRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for PairKeyRequest {
     const WRITE_TYPE: WriteType = WriteType::WithResponse;
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BatteryRequest {
+    #[br(assert(id == CommandId::Battery))]
     #[bw(assert(*id == CommandId::Battery))]
     pub id: CommandId
 }
@@ -63,13 +77,21 @@ impl BatteryRequest {
     }
 }
 
+impl Default for BatteryRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for BatteryRequest {
     const WRITE_TYPE: WriteType = WriteType::WithResponse;
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetDateTimeRequest {
+    #[br(assert(id == CommandId::DateTime))]
     #[bw(assert(*id == CommandId::DateTime))]
     pub id: CommandId,
     pub date: Date,
@@ -88,6 +110,14 @@ impl SetDateTimeRequest {
             sec
         }
     }
+
+    pub fn try_new(date: Date, hour: u8, min: u8, sec: u8) -> Result<Self> {
+        if (hour >= 24) || (min >= 60) || (sec >= 60) {
+            return Err(datetime::invalid_time(hour, min, sec));
+        }
+
+        Ok(Self::new(date.checked()?, hour, min, sec))
+    }
 }
 
 impl CommandRequest for SetDateTimeRequest {
@@ -95,8 +125,10 @@ impl CommandRequest for SetDateTimeRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FirmwareRequest {
+    #[br(assert(id == CommandId::Firmware))]
     #[bw(assert(*id == CommandId::Firmware))]
     pub id: CommandId
 }
@@ -109,14 +141,22 @@ impl FirmwareRequest {
     }
 }
 
+impl Default for FirmwareRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for FirmwareRequest {
     const WRITE_TYPE: WriteType = WriteType::WithResponse;
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResetRequest {
-    #[bw(assert(*id == CommandId::Firmware))]
+    #[br(assert(id == CommandId::Reset))]
+    #[bw(assert(*id == CommandId::Reset))]
     pub id: CommandId,
     pub sub_id: ResetCommandSubId
 }
@@ -135,8 +175,10 @@ impl CommandRequest for ResetRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConfigDisplayFormatsRequest {
+    #[br(assert(id == CommandId::Config))]
     #[bw(assert(*id == CommandId::Config))]
     pub id: CommandId,
     pub distance_unit: DistanceUnit,
@@ -158,25 +200,30 @@ impl CommandRequest for ConfigDisplayFormatsRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlertStartBatchRequest {
+    #[br(assert(id == CommandId::Alert))]
     #[bw(assert(*id == CommandId::Alert))]
     pub id: CommandId,
+    #[br(assert(batch_idx == 0))]
     #[bw(assert(*batch_idx == 0))]
     pub batch_idx: u8,
     pub alert_type: AlertType,
     pub msg_full_len_bytes: u8,
-    pub msg_start_text_utf16be: Vec<u16>
+    #[br(big, parse_with = binrw::helpers::until_eof)]
+    #[bw(big)]
+    pub msg_start_text_utf16: Vec<u16>
 }
 
 impl AlertStartBatchRequest {
-    pub const fn new(alert_type: AlertType, msg_full_len_bytes: u8, msg_start_text_utf16be: Vec<u16>) -> Self {
+    pub const fn new(alert_type: AlertType, msg_full_len_bytes: u8, msg_start_text_utf16: Vec<u16>) -> Self {
         Self {
             id: CommandId::Alert,
             batch_idx: 0,
             alert_type,
             msg_full_len_bytes,
-            msg_start_text_utf16be
+            msg_start_text_utf16
         }
     }
 }
@@ -186,21 +233,26 @@ impl CommandRequest for AlertStartBatchRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite, Default)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlertNextBatchRequest {
+    #[br(assert(id == CommandId::Alert))]
     #[bw(assert(*id == CommandId::Alert))]
     pub id: CommandId,
+    #[br(assert(batch_idx > 0))]
     #[bw(assert(*batch_idx > 0))]
     pub batch_idx: u8,
-    pub msg_next_text_utf16be: Vec<u16>
+    #[br(big, parse_with = binrw::helpers::until_eof)]
+    #[bw(big)]
+    pub msg_next_text_utf16: Vec<u16>
 }
 
 impl AlertNextBatchRequest {
-    pub const fn new(batch_idx: u8, msg_next_text_utf16be: Vec<u16>) -> Self {
+    pub const fn new(batch_idx: u8, msg_next_text_utf16: Vec<u16>) -> Self {
         Self {
             id: CommandId::Alert,
             batch_idx,
-            msg_next_text_utf16be
+            msg_next_text_utf16
         }
     }
 }
@@ -210,10 +262,13 @@ impl CommandRequest for AlertNextBatchRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlertPushRequest {
+    #[br(assert(id == CommandId::Alert))]
     #[bw(assert(*id == CommandId::Alert))]
     pub id: CommandId,
+    #[br(assert(push_ident == Self::PUSH_IDENT_VALUE))]
     #[bw(assert(*push_ident == Self::PUSH_IDENT_VALUE))]
     pub push_ident: u8
 }
@@ -229,47 +284,78 @@ impl AlertPushRequest {
     }
 }
 
+impl Default for AlertPushRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for AlertPushRequest {
     const WRITE_TYPE: WriteType = WriteType::WithResponse;
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-pub async fn write_send_alert(watch: &Peripheral, alert_type: AlertType, text: String) -> Result<(), Box<dyn Error>> {
-    let mut text_utf16_be = text.encode_utf16().map(|ch| ch.swap_bytes()).collect::<VecDeque<_>>();
+// Whole alert, sent as a start batch, next batches and a final push
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlertRequest {
+    pub alert_type: AlertType,
+    pub text_utf16: Vec<u16>
+}
+
+impl AlertRequest {
+    // The full message length (in bytes) is sent as a single byte
+    pub const MAX_TEXT_LEN_BYTES: usize = u8::MAX as usize;
+    pub const MAX_TEXT_LEN: usize = Self::MAX_TEXT_LEN_BYTES / size_of::<u16>();
 
-    let mut alert_batch_0_req = AlertStartBatchRequest::new(alert_type, (text_utf16_be.len() * size_of::<u16>()) as u8, Vec::new());
-    while alert_batch_0_req.len() < super::MAX_WRITE_SIZE {
-        if let Some(next_ch) = text_utf16_be.pop_front() {
-            alert_batch_0_req.msg_start_text_utf16be.push(next_ch);
-        }
-        else {
-            break;
+    pub fn new(alert_type: AlertType, text: &str) -> Self {
+        Self {
+            alert_type,
+            text_utf16: text.encode_utf16().collect()
         }
     }
-    write::<AlertStartBatchRequest>(watch, alert_batch_0_req).await?;
-
-    let mut batch_idx = 1u8;
-    while !text_utf16_be.is_empty() {
-        let mut alert_batch_i_req = AlertNextBatchRequest::new(batch_idx, Vec::new());
-
-        while alert_batch_i_req.len() < super::MAX_WRITE_SIZE {
-            if let Some(next_ch) = text_utf16_be.pop_front() {
-                alert_batch_i_req.msg_next_text_utf16be.push(next_ch);
-            }
-            else {
-                break;
-            }
-        }
-        write::<AlertNextBatchRequest>(watch, alert_batch_i_req).await?;
-        batch_idx += 1;
+
+    pub fn text_len_bytes(&self) -> usize {
+        self.text_utf16.len() * size_of::<u16>()
+    }
+}
+
+impl MultiFrameRequest for AlertRequest {
+    const WRITE_TYPE: WriteType = WriteType::WithResponse;
+    const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
+    const UNIT_SIZE: usize = size_of::<u16>();
+
+    fn unit_count(&self) -> usize {
+        self.text_utf16.len()
+    }
+
+    // Never split a surrogate pair between frames
+    fn is_unit_boundary(&self, unit_idx: usize) -> bool {
+        !(0xDC00..=0xDFFF).contains(&self.text_utf16[unit_idx])
+    }
+
+    fn start_frame(&self, units: Range<usize>) -> Result<Vec<u8>> {
+        let msg_full_len_bytes = u8::try_from(self.text_len_bytes()).map_err(|_| Error::PayloadTooLarge { len: self.text_len_bytes(), max: Self::MAX_TEXT_LEN_BYTES })?;
+        AlertStartBatchRequest::new(self.alert_type, msg_full_len_bytes, self.text_utf16[units].to_vec()).build()
+    }
+
+    fn next_frame(&self, frame_idx: u8, units: Range<usize>) -> Result<Vec<u8>> {
+        AlertNextBatchRequest::new(frame_idx, self.text_utf16[units].to_vec()).build()
+    }
+
+    fn push_frame(&self) -> Result<Option<Vec<u8>>> {
+        Ok(Some(AlertPushRequest::new().build()?))
     }
+}
 
-    write::<AlertPushRequest>(watch, AlertPushRequest::new()).await?;
-    Ok(())
+pub async fn write_send_alert(watch: &impl WatchTransport, alert_type: AlertType, text: String) -> Result<()> {
+    write_multi_frame(watch, &AlertRequest::new(alert_type, &text)).await
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetUserInfoRequest {
+    #[br(assert(id == CommandId::UserInfo))]
     #[bw(assert(*id == CommandId::UserInfo))]
     pub id: CommandId,
     pub unk0: u8,
@@ -279,7 +365,8 @@ pub struct SetUserInfoRequest {
     pub screen_show_timeout_seconds: u8,
     pub unk2: u8,
     pub unk3: u8,
-    pub step_goal_be: u16,
+    #[brw(big)]
+    pub step_goal: u16,
     pub lift_wrist_mode: UserLiftWristMode,
     pub unk4: u8,
     pub unk5: u8,
@@ -292,28 +379,47 @@ pub struct SetUserInfoRequest {
 }
 
 impl SetUserInfoRequest {
+    // Note: default unk values are the same ones Hello Haylou app uses
+    // (TODO: are they any meaningful fields as well? probably... see UserInfo for experimenting with them)
+    pub const DEFAULT_UNKS: [u8; 10] = [0, 0, 0, 0, 160, 0, 0, 1, 1, 40];
+
     pub const fn new(height_cm: u8, weight_kg: u8, screen_show_timeout_seconds: u8, step_goal: u16, lift_wrist_mode: UserLiftWristMode, age: u8, gender: UserGender) -> Self {
-        // Note: default unk values are the same ones Hello Haylou app uses
-        // (TODO: are they any meaningful fields as well? probably...)
         Self {
             id: CommandId::UserInfo,
-            unk0: 0,
+            unk0: Self::DEFAULT_UNKS[0],
             height_cm,
-            unk1: 0,
+            unk1: Self::DEFAULT_UNKS[1],
             weight_kg,
             screen_show_timeout_seconds,
-            unk2: 0,
-            unk3: 0,
-            step_goal_be: step_goal.swap_bytes(),
+            unk2: Self::DEFAULT_UNKS[2],
+            unk3: Self::DEFAULT_UNKS[3],
+            step_goal,
             lift_wrist_mode,
-            unk4: 160,
-            unk5: 0,
+            unk4: Self::DEFAULT_UNKS[4],
+            unk5: Self::DEFAULT_UNKS[5],
             age,
             gender,
-            unk6: 0,
-            unk7: 1,
-            unk8: 1,
-            unk9: 40
+            unk6: Self::DEFAULT_UNKS[6],
+            unk7: Self::DEFAULT_UNKS[7],
+            unk8: Self::DEFAULT_UNKS[8],
+            unk9: Self::DEFAULT_UNKS[9]
+        }
+    }
+
+    // unk0-unk9 by index
+    pub fn unk_mut(&mut self, idx: usize) -> Option<&mut u8> {
+        match idx {
+            0 => Some(&mut self.unk0),
+            1 => Some(&mut self.unk1),
+            2 => Some(&mut self.unk2),
+            3 => Some(&mut self.unk3),
+            4 => Some(&mut self.unk4),
+            5 => Some(&mut self.unk5),
+            6 => Some(&mut self.unk6),
+            7 => Some(&mut self.unk7),
+            8 => Some(&mut self.unk8),
+            9 => Some(&mut self.unk9),
+            _ => None
         }
     }
 }
@@ -323,21 +429,24 @@ impl CommandRequest for SetUserInfoRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetWeatherTodayRequest {
+    #[br(assert(id == CommandId::Weather))]
     #[bw(assert(*id == CommandId::Weather))]
     pub id: CommandId,
+    #[br(assert(weather_date == WeatherDate::Today))]
     #[bw(assert(*weather_date == WeatherDate::Today))]
     pub weather_date: WeatherDate,
     pub weather_type: WeatherType,
     pub unk: u8,
-    pub cur_temperature: u8,
-    pub max_temperature: u8,
-    pub min_temperature: u8
+    pub cur_temperature: i8,
+    pub max_temperature: i8,
+    pub min_temperature: i8
 }
 
 impl SetWeatherTodayRequest {
-    pub const fn new(weather_type: WeatherType, cur_temperature: u8, max_temperature: u8, min_temperature: u8) -> Self {
+    pub const fn new(weather_type: WeatherType, cur_temperature: i8, max_temperature: i8, min_temperature: i8) -> Self {
         Self {
             id: CommandId::Weather,
             weather_date: WeatherDate::Today,
@@ -355,30 +464,34 @@ impl CommandRequest for SetWeatherTodayRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, BinWrite)]
+#[derive(Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetWeatherFollowingDaysRequest {
+    #[br(assert(id == CommandId::Weather))]
     #[bw(assert(*id == CommandId::Weather))]
     pub id: CommandId,
+    #[br(assert(weather_date == WeatherDate::FollowingDays))]
     #[bw(assert(*weather_date == WeatherDate::FollowingDays))]
     pub weather_date: WeatherDate,
     pub next_1_weather_type: WeatherType,
     pub next_1_unk: u8,
-    pub next_1_max_temperature: u8,
-    pub next_1_min_temperature: u8,
+    pub next_1_max_temperature: i8,
+    pub next_1_min_temperature: i8,
 
     pub next_2_weather_type: WeatherType,
     pub next_2_unk: u8,
-    pub next_2_max_temperature: u8,
-    pub next_2_min_temperature: u8,
+    pub next_2_max_temperature: i8,
+    pub next_2_min_temperature: i8,
 
     pub next_3_weather_type: WeatherType,
     pub next_3_unk: u8,
-    pub next_3_max_temperature: u8,
-    pub next_3_min_temperature: u8
+    pub next_3_max_temperature: i8,
+    pub next_3_min_temperature: i8
 }
 
 impl SetWeatherFollowingDaysRequest {
-    pub const fn new(next_1_weather_type: WeatherType, next_1_max_temperature: u8, next_1_min_temperature: u8, next_2_weather_type: WeatherType, next_2_max_temperature: u8, next_2_min_temperature: u8, next_3_weather_type: WeatherType, next_3_max_temperature: u8, next_3_min_temperature: u8) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(next_1_weather_type: WeatherType, next_1_max_temperature: i8, next_1_min_temperature: i8, next_2_weather_type: WeatherType, next_2_max_temperature: i8, next_2_min_temperature: i8, next_3_weather_type: WeatherType, next_3_max_temperature: i8, next_3_min_temperature: i8) -> Self {
         Self {
             id: CommandId::Weather,
             weather_date: WeatherDate::FollowingDays,
@@ -406,13 +519,16 @@ impl CommandRequest for SetWeatherFollowingDaysRequest {
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinWrite)]
-
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsRequest {
+    #[br(assert(id == CommandId::RecordedStepsGeneral))]
     #[bw(assert(*id == CommandId::RecordedStepsGeneral))]
     pub id: CommandId,
+    #[br(assert(sub_id == RecordedStepsCommandSubId::Request))]
     #[bw(assert(*sub_id == RecordedStepsCommandSubId::Request))]
     pub sub_id: RecordedStepsCommandSubId,
+    #[br(assert(unk == 1))]
     #[bw(assert(*unk == 1))]
     pub unk: u8
 }
@@ -427,6 +543,12 @@ impl RecordedStepsRequest {
     }
 }
 
+impl Default for RecordedStepsRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandRequest for RecordedStepsRequest {
     const WRITE_TYPE: WriteType = WriteType::WithResponse;
     const CHAR: Characteristic = super::CHAR_GENERAL_RW_1;
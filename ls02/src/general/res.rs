@@ -1,7 +1,8 @@
 use super::*;
 use super::common::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PairKeyResponse {
     #[br(assert(id == CommandId::Pair))]
     pub id: CommandId,
@@ -14,7 +15,8 @@ impl CommandResponse for PairKeyResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BatteryResponse {
     #[br(assert(id == CommandId::Battery))]
     pub id: CommandId,
@@ -25,13 +27,12 @@ impl CommandResponse for BatteryResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetDateTimeResponse {
     #[br(assert(id == CommandId::DateTime))]
     pub id: CommandId,
-    pub year_be: u16,
-    pub month: u8,
-    pub day: u8,
+    pub date: Date,
     pub hour: u8,
     pub min: u8,
     pub sec: u8
@@ -41,7 +42,8 @@ impl CommandResponse for SetDateTimeResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FirmwareResponse {
     #[br(assert(id == CommandId::Firmware))]
     pub id: CommandId,
@@ -52,7 +54,21 @@ impl CommandResponse for FirmwareResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigDisplayFormatsResponse {
+    #[br(assert(id == CommandId::Config))]
+    pub id: CommandId,
+    pub distance_unit: DistanceUnit,
+    pub time_fmt: TimeFormat
+}
+
+impl CommandResponse for ConfigDisplayFormatsResponse {
+    const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DevicePulseResponse {
     #[br(assert(id == CommandId::DevicePulse))]
     pub id: CommandId,
@@ -63,7 +79,8 @@ impl CommandResponse for DevicePulseResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetWeatherResponse {
     #[br(assert(id == CommandId::Weather))]
     pub id: CommandId,
@@ -74,7 +91,8 @@ impl CommandResponse for SetWeatherResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMenuDataPeriodicResponse {
     #[br(assert(id == CommandId::HeartRateMenuGeneral))]
     pub id: CommandId,
@@ -88,7 +106,8 @@ impl CommandResponse for HeartRateMenuDataPeriodicResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMenuDataLeavingResponse {
     #[br(assert(id == CommandId::HeartRateMenuGeneral))]
     pub id: CommandId,
@@ -102,7 +121,8 @@ impl CommandResponse for HeartRateMenuDataLeavingResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMenuChangeResponse {
     #[br(assert(id == CommandId::HeartRateMenuGeneral))]
     pub id: CommandId,
@@ -114,7 +134,8 @@ impl CommandResponse for HeartRateMenuChangeResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SilentModeChangeResponse {
     #[br(assert(id == CommandId::SilentMode))]
     pub id: CommandId,
@@ -128,7 +149,8 @@ impl CommandResponse for SilentModeChangeResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SportStatusResponse {
     #[br(assert(id == CommandId::Sport))]
     pub id: CommandId,
@@ -141,7 +163,8 @@ impl CommandResponse for SportStatusResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsEntryResponse {
     #[br(assert(id == CommandId::RecordedStepsGeneral))]
     pub id: CommandId,
@@ -152,7 +175,8 @@ impl CommandResponse for RecordedStepsEntryResponse {
     const CHAR: Characteristic = super::CHAR_GENERAL_N_1;
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordedStepsEndResponse {
     #[br(assert(id == CommandId::RecordedStepsGeneral))]
     pub id: CommandId,
@@ -0,0 +1,96 @@
+use std::ops::RangeInclusive;
+use super::*;
+use super::common::*;
+use super::req::SetUserInfoRequest;
+
+// Builds (and validates) the user information sent to the watch
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserInfo {
+    pub height_cm: u8,
+    pub weight_kg: u8,
+    pub screen_show_timeout_seconds: u8,
+    pub step_goal: u16,
+    pub lift_wrist_mode: UserLiftWristMode,
+    pub age: u8,
+    pub gender: UserGender,
+    // Raw values for unk0-unk9, the ones in SetUserInfoRequest::DEFAULT_UNKS being used otherwise
+    pub unk_overrides: [Option<u8>; 10]
+}
+
+impl UserInfo {
+    // Values outside these ranges are rejected, even if the watch might accept (some of) them
+    pub const HEIGHT_CM_RANGE: RangeInclusive<u8> = 50..=250;
+    pub const WEIGHT_KG_RANGE: RangeInclusive<u8> = 20..=250;
+    pub const SCREEN_SHOW_TIMEOUT_SECONDS_RANGE: RangeInclusive<u8> = 5..=15;
+    pub const STEP_GOAL_RANGE: RangeInclusive<u16> = 1000..=50000;
+    pub const AGE_RANGE: RangeInclusive<u8> = 1..=120;
+
+    pub const fn new(height_cm: u8, weight_kg: u8, age: u8, gender: UserGender) -> Self {
+        Self {
+            height_cm,
+            weight_kg,
+            screen_show_timeout_seconds: 5,
+            step_goal: 8000,
+            lift_wrist_mode: UserLiftWristMode::On,
+            age,
+            gender,
+            unk_overrides: [None; 10]
+        }
+    }
+
+    pub fn with_screen_show_timeout_seconds(mut self, screen_show_timeout_seconds: u8) -> Self {
+        self.screen_show_timeout_seconds = screen_show_timeout_seconds;
+        self
+    }
+
+    pub fn with_step_goal(mut self, step_goal: u16) -> Self {
+        self.step_goal = step_goal;
+        self
+    }
+
+    pub fn with_lift_wrist_mode(mut self, lift_wrist_mode: UserLiftWristMode) -> Self {
+        self.lift_wrist_mode = lift_wrist_mode;
+        self
+    }
+
+    // For experimenting with what the unknown bytes do (idx being the N in unkN), at your own risk
+    pub fn with_raw_unk(mut self, idx: usize, value: u8) -> Result<Self> {
+        let unk_count = self.unk_overrides.len();
+        match self.unk_overrides.get_mut(idx) {
+            Some(unk_override) => {
+                *unk_override = Some(value);
+                Ok(self)
+            },
+            None => Err(Error::InvalidArgument(format!("There is no unk{} (only unk0..=unk{})", idx, unk_count - 1)))
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        fn check<T: PartialOrd + std::fmt::Display>(name: &str, value: T, range: RangeInclusive<T>) -> Result<()> {
+            match range.contains(&value) {
+                true => Ok(()),
+                false => Err(Error::InvalidArgument(format!("{} must be in {}..={}, got {}", name, range.start(), range.end(), value)))
+            }
+        }
+
+        check("Height (cm)", self.height_cm, Self::HEIGHT_CM_RANGE)?;
+        check("Weight (kg)", self.weight_kg, Self::WEIGHT_KG_RANGE)?;
+        check("Screen show timeout (seconds)", self.screen_show_timeout_seconds, Self::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE)?;
+        check("Step goal", self.step_goal, Self::STEP_GOAL_RANGE)?;
+        check("Age", self.age, Self::AGE_RANGE)?;
+        Ok(())
+    }
+
+    pub fn build(&self) -> Result<SetUserInfoRequest> {
+        self.validate()?;
+
+        let mut req = SetUserInfoRequest::new(self.height_cm, self.weight_kg, self.screen_show_timeout_seconds, self.step_goal, self.lift_wrist_mode, self.age, self.gender);
+        for (idx, unk_override) in self.unk_overrides.iter().enumerate() {
+            if let (Some(value), Some(unk)) = (unk_override, req.unk_mut(idx)) {
+                *unk = *value;
+            }
+        }
+        Ok(req)
+    }
+}
@@ -0,0 +1,150 @@
+use super::*;
+use super::common::*;
+use futures::stream::StreamExt;
+use tokio::time::{self, Instant};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateSummary {
+    pub date: Date,
+    pub hour: u8,
+    pub min: u8,
+    pub max_heart_rate: u8,
+    pub min_heart_rate: u8,
+    pub avg_heart_rate: u8
+}
+
+// Covers 2 hours (starting at an even hour), one heart rate each 10min
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateHistoryEntry {
+    pub date: Date,
+    pub hour: u8,
+    pub heart_rates: [u8; 12]
+}
+
+impl HeartRateHistoryEntry {
+    pub const SAMPLE_INTERVAL_MINS: u32 = 10;
+
+    // (hour, min, heart rate) for every measured sample
+    pub fn samples(&self) -> impl Iterator<Item = (u8, u8, u8)> + '_ {
+        self.heart_rates.iter().enumerate()
+            .filter(|(_, heart_rate)| **heart_rate != INVALID_HEART_RATE)
+            .map(|(i, heart_rate)| {
+                let time = self.minute_of_day() + i as u32 * Self::SAMPLE_INTERVAL_MINS;
+                ((time / 60) as u8, (time % 60) as u8, *heart_rate)
+            })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateHistory {
+    pub today: Option<HeartRateSummary>,
+    pub entries: Vec<HeartRateHistoryEntry>
+}
+
+impl HeartRateHistory {
+    pub fn dates(&self) -> Vec<Date> {
+        let mut dates = self.entries.iter().map(|entry| entry.date).chain(self.today.map(|today| today.date)).collect::<Vec<_>>();
+        dates.dedup();
+        dates
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StepHistory {
+    pub entries: Vec<StepsEntry>
+}
+
+enum HistoryFrame {
+    Other,
+    Entry,
+    End
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // The timeout applies to the gap between history frames, not to the whole dump (which may take a while)
+    async fn collect_history<C: CommandRequest + Send>(&self, cmd: C, mut on_response: impl FnMut(Response) -> HistoryFrame + Send) -> Result<()> where for<'a> <C as BinWrite>::Args<'a>: Default {
+        let mut notif_stream = self.transport().notifications().await?;
+        self.send(cmd).await?;
+
+        let mut deadline = Instant::now() + self.timeout();
+        loop {
+            let value_notif = match time::timeout_at(deadline, notif_stream.next()).await {
+                Ok(Some(value_notif)) => value_notif,
+                Ok(None) => return Err(Error::NotificationStreamClosed),
+                Err(_) => return Err(Error::Timeout(self.timeout()))
+            };
+
+            match on_response(Response::from_notification(&value_notif)) {
+                HistoryFrame::Other => {},
+                HistoryFrame::Entry => deadline = Instant::now() + self.timeout(),
+                HistoryFrame::End => return Ok(())
+            }
+        }
+    }
+
+    // Progress is reported with the amount of entries received so far
+    pub async fn sync_heart_rate(&self, mut progress: impl FnMut(usize) + Send) -> Result<HeartRateHistory> {
+        let mut history = HeartRateHistory::default();
+        self.collect_history(data2::req::HeartRateDataRequest::new(), |res| match res {
+            Response::HeartRateDataToday(today_res) => {
+                history.today = Some(HeartRateSummary {
+                    date: today_res.date,
+                    hour: today_res.hour,
+                    min: today_res.min,
+                    max_heart_rate: today_res.max_heart_rate,
+                    min_heart_rate: today_res.min_heart_rate,
+                    avg_heart_rate: today_res.avg_heart_rate
+                });
+                HistoryFrame::Entry
+            },
+            Response::HeartRateDataDayHourEntry(entry_res) => {
+                history.entries.push(HeartRateHistoryEntry {
+                    date: entry_res.date,
+                    hour: entry_res.hour,
+                    heart_rates: entry_res.heart_rates
+                });
+                progress(history.entries.len());
+                HistoryFrame::Entry
+            },
+            Response::HeartRateDataEnd(_) => HistoryFrame::End,
+            _ => HistoryFrame::Other
+        }).await?;
+
+        Ok(history)
+    }
+
+    // Both request variants return the same data: the Data2 one is tried first, falling back to the General one if the watch doesn't respond at all
+    pub async fn sync_recorded_steps(&self, mut progress: impl FnMut(usize) + Send) -> Result<StepHistory> {
+        let mut history = StepHistory::default();
+        let res = self.collect_history(data2::req::RecordedStepsRequest::new(), |res| match res {
+            Response::RecordedStepsEntryData2(entry_res) => {
+                history.entries.push(entry_res.entry);
+                progress(history.entries.len());
+                HistoryFrame::Entry
+            },
+            Response::RecordedStepsEndData2(_) => HistoryFrame::End,
+            _ => HistoryFrame::Other
+        }).await;
+
+        match res {
+            Err(Error::Timeout(_)) if history.entries.is_empty() => {},
+            res => return res.map(|_| history)
+        };
+
+        self.collect_history(general::req::RecordedStepsRequest::new(), |res| match res {
+            Response::RecordedStepsEntryGeneral(entry_res) => {
+                history.entries.push(entry_res.entry);
+                progress(history.entries.len());
+                HistoryFrame::Entry
+            },
+            Response::RecordedStepsEndGeneral(_) => HistoryFrame::End,
+            _ => HistoryFrame::Other
+        }).await?;
+
+        Ok(history)
+    }
+}
@@ -1,8 +1,8 @@
 use uuid::{Uuid, uuid};
 use std::collections::BTreeSet;
-use std::error::Error;
+use std::ops::Range;
 use binrw::{BinRead, BinReaderExt, BinWrite, BinWriterExt, io::Cursor};
-use btleplug::api::{Peripheral as _, WriteType, Characteristic, CharPropFlags, ValueNotification};
+use btleplug::api::{WriteType, Characteristic, CharPropFlags, ValueNotification};
 use btleplug::platform::Peripheral;
 
 pub const DEVICE_NAME: &str = "Haylou Smart Watch 2";
@@ -85,61 +85,269 @@ pub const CHAR_DATA3_N: Characteristic = Characteristic {
     descriptors: BTreeSet::new()
 };
 
+// Standard BLE Battery Service, also present on the watch
+pub const SERVICE_BATTERY: Uuid = uuid!("0000180F-0000-1000-8000-00805F9B34FB");
+
+pub const CHAR_BATTERY_LEVEL: Characteristic = Characteristic {
+    uuid: uuid!("00002A19-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_BATTERY,
+    properties: CharPropFlags::READ.union(CharPropFlags::NOTIFY),
+    descriptors: BTreeSet::new()
+};
+
+// Vendor service (probably OTA/device info related), only its readable characteristics are defined, see vendor::probe
+pub const SERVICE_VENDOR: Uuid = uuid!("0000D0FF-3C17-D293-8E48-14FE2E4DA212");
+
+pub const CHAR_VENDOR_FFD2: Characteristic = Characteristic {
+    uuid: uuid!("0000FFD2-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
+pub const CHAR_VENDOR_FFD3: Characteristic = Characteristic {
+    uuid: uuid!("0000FFD3-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
+pub const CHAR_VENDOR_FFD4: Characteristic = Characteristic {
+    uuid: uuid!("0000FFD4-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
+pub const CHAR_VENDOR_FFD5: Characteristic = Characteristic {
+    uuid: uuid!("0000FFD5-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
+pub const CHAR_VENDOR_FFE0: Characteristic = Characteristic {
+    uuid: uuid!("0000FFE0-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
+pub const CHAR_VENDOR_FFF1: Characteristic = Characteristic {
+    uuid: uuid!("0000FFF1-0000-1000-8000-00805F9B34FB"),
+    service_uuid: SERVICE_VENDOR,
+    properties: CharPropFlags::READ,
+    descriptors: BTreeSet::new()
+};
+
 pub const MAX_WRITE_SIZE: usize = 48;
 
+#[allow(clippy::len_without_is_empty)]
 pub trait CommandRequest: Sized + BinWrite where for<'a> <Self as BinWrite>::Args<'a>: Default {
     const WRITE_TYPE: WriteType;
     const CHAR: Characteristic;
 
-    fn build(&self) -> Vec<u8> {
+    fn build(&self) -> Result<Vec<u8>> {
         let mut cmd: Vec<u8> = Vec::new();
         let mut cmd_writer = Cursor::new(&mut cmd);
 
-        cmd_writer.write_le(self).unwrap();
+        cmd_writer.write_le(self)?;
 
-        cmd
+        Ok(cmd)
     }
 
-    fn len(&self) -> usize {
-        self.build().len()
+    fn len(&self) -> Result<usize> {
+        Ok(self.build()?.len())
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum ParseMode {
+    // Unknown enum values are kept (see common::lenient_enum!) and trailing bytes are returned, so that newer firmwares don't break parsing
+    #[default]
+    Lenient,
+    // Anything not exactly matching what is known is rejected, useful for reverse-engineering
+    Strict
+}
+
 pub trait CommandResponse: Sized + BinRead where <Self as BinRead>::Args<'static>: Default {
     const CHAR: Characteristic;
 
-    fn try_parse(raw: &Vec<u8>) -> Option<Self> {
+    // The response is returned along with any bytes following it (always empty in strict mode)
+    fn parse_with(raw: &[u8], mode: ParseMode) -> Result<(Self, Vec<u8>)> {
         let mut cmd_reader = Cursor::new(raw);
-        let res_opt = cmd_reader.read_le::<Self>().ok();
-        if cmd_reader.read_le::<u8>().is_err() {
-            res_opt
-        }
-        else {
-            None
+        let res = common::with_parse_mode(mode, || cmd_reader.read_le::<Self>()).map_err(|_| Error::Decode { char: Self::CHAR.uuid, bytes: raw.to_vec() })?;
+
+        let parsed_len = cmd_reader.position() as usize;
+        if (mode == ParseMode::Strict) && (parsed_len < raw.len()) {
+            return Err(Error::TrailingBytes { char: Self::CHAR.uuid, bytes: raw.to_vec(), parsed_len });
         }
+        Ok((res, raw[parsed_len..].to_vec()))
+    }
+
+    #[inline]
+    fn parse(raw: &[u8]) -> Result<Self> {
+        Self::parse_with(raw, ParseMode::Strict).map(|(res, _)| res)
+    }
+
+    #[inline]
+    fn parse_lenient(raw: &[u8]) -> Result<Self> {
+        Self::parse_with(raw, ParseMode::Lenient).map(|(res, _)| res)
+    }
+
+    #[inline]
+    fn try_parse(raw: &[u8]) -> Option<Self> {
+        Self::parse_lenient(raw).ok()
+    }
+}
+
+// MAX_WRITE_SIZE is used unless the transport knows the negotiated MTU
+pub async fn max_write_size(watch: &impl WatchTransport) -> usize {
+    match watch.mtu().await {
+        Some(mtu) => mtu.saturating_sub(transport::ATT_HEADER_SIZE),
+        None => MAX_WRITE_SIZE
     }
 }
 
-#[inline]
-pub async fn write<C: CommandRequest>(watch: &Peripheral, cmd: C) -> Result<(), Box<dyn Error>> where for<'a> <C as BinWrite>::Args<'a>: Default {
-    watch.write(&C::CHAR, &cmd.build(), C::WRITE_TYPE).await?;
+pub async fn write<C: CommandRequest>(watch: &impl WatchTransport, cmd: C) -> Result<()> where for<'a> <C as BinWrite>::Args<'a>: Default {
+    write_with_max_size(watch, cmd, max_write_size(watch).await).await
+}
+
+// For when the max write size is known better than the transport does (see Watch::with_mtu())
+pub async fn write_with_max_size<C: CommandRequest>(watch: &impl WatchTransport, cmd: C, max_size: usize) -> Result<()> where for<'a> <C as BinWrite>::Args<'a>: Default {
+    let cmd_data = cmd.build()?;
+
+    if cmd_data.len() > max_size {
+        return Err(Error::PayloadTooLarge { len: cmd_data.len(), max: max_size });
+    }
+
+    watch.write(&C::CHAR, &cmd_data, C::WRITE_TYPE).await?;
     Ok(())
 }
 
-pub enum CommandResponseResult<C: CommandResponse> where <C as BinRead>::Args<'static>: Default {
-    Invalid,
-    ImproperCharacteristic,
-    Ok(C)
+// Requests whose payload (made of fixed-size units, which are never split) may need to be sent across several frames:
+// a start frame (with the first units), next frames (with the following units) and optionally a final push frame
+pub trait MultiFrameRequest {
+    const WRITE_TYPE: WriteType;
+    const CHAR: Characteristic;
+    const UNIT_SIZE: usize;
+    // Only frames 0x00-0xFC are allowed, 0xFD being the push frame (see RE.md)
+    const MAX_FRAME_IDX: u8 = 0xFC;
+
+    fn unit_count(&self) -> usize;
+
+    // Whether a frame may start at the given unit
+    fn is_unit_boundary(&self, _unit_idx: usize) -> bool {
+        true
+    }
+
+    fn start_frame(&self, units: Range<usize>) -> Result<Vec<u8>>;
+
+    // Frame indexes start at 1 (the start frame being 0)
+    fn next_frame(&self, frame_idx: u8, units: Range<usize>) -> Result<Vec<u8>>;
+
+    fn push_frame(&self) -> Result<Option<Vec<u8>>>;
+
+    fn fragment(&self, max_frame_size: usize) -> Result<Vec<Vec<u8>>> {
+        // Frame headers are encoded once (with no units) to know how many units fit in each frame
+        let units_per_frame = |header_size: usize| {
+            match max_frame_size.saturating_sub(header_size) / Self::UNIT_SIZE {
+                0 => Err(Error::PayloadTooLarge { len: header_size + Self::UNIT_SIZE, max: max_frame_size }),
+                unit_count => Ok(unit_count)
+            }
+        };
+        let start_units = units_per_frame(self.start_frame(0..0)?.len())?;
+        let next_units = units_per_frame(self.next_frame(1, 0..0)?.len())?;
+
+        let unit_count = self.unit_count();
+        let frame_end = |frame_start: usize, frame_units: usize| {
+            let mut frame_end = (frame_start + frame_units).min(unit_count);
+            while (frame_end < unit_count) && (frame_end > frame_start + 1) && !self.is_unit_boundary(frame_end) {
+                frame_end -= 1;
+            }
+            frame_end
+        };
+
+        let mut cur_unit = frame_end(0, start_units);
+        let mut frames = vec![self.start_frame(0..cur_unit)?];
+
+        let mut frame_idx = 0u8;
+        while cur_unit < unit_count {
+            if frame_idx == Self::MAX_FRAME_IDX {
+                return Err(Error::PayloadTooLarge { len: unit_count * Self::UNIT_SIZE, max: (start_units + Self::MAX_FRAME_IDX as usize * next_units) * Self::UNIT_SIZE });
+            }
+            frame_idx += 1;
+
+            let next_unit = frame_end(cur_unit, next_units);
+            frames.push(self.next_frame(frame_idx, cur_unit..next_unit)?);
+            cur_unit = next_unit;
+        }
+
+        if let Some(push_frame) = self.push_frame()? {
+            frames.push(push_frame);
+        }
+        Ok(frames)
+    }
+}
+
+pub async fn write_multi_frame<M: MultiFrameRequest>(watch: &impl WatchTransport, req: &M) -> Result<()> {
+    write_multi_frame_with_max_size(watch, req, max_write_size(watch).await).await
 }
 
-#[inline]
-pub async fn read<'a, C: CommandResponse>(value_notif: &ValueNotification, notif_char: &Characteristic) -> CommandResponseResult<C> where <C as BinRead>::Args<'static>: Default {
-    match C::try_parse(&value_notif.value) {
-        Some(res) => if C::CHAR.uuid.eq(&notif_char.uuid) { CommandResponseResult::Ok(res) } else { CommandResponseResult::ImproperCharacteristic },
-        None => CommandResponseResult::Invalid
+pub async fn write_multi_frame_with_max_size<M: MultiFrameRequest>(watch: &impl WatchTransport, req: &M, max_size: usize) -> Result<()> {
+    for frame in req.fragment(max_size)? {
+        watch.write(&M::CHAR, &frame, M::WRITE_TYPE).await?;
     }
+    Ok(())
+}
+
+pub fn read<C: CommandResponse>(value_notif: &ValueNotification) -> Result<C> where <C as BinRead>::Args<'static>: Default {
+    if value_notif.uuid != C::CHAR.uuid {
+        return Err(Error::UnexpectedCharacteristic { expected: C::CHAR.uuid, got: value_notif.uuid });
+    }
+
+    C::parse(&value_notif.value)
 }
 
+pub mod error;
+pub use error::{Error, Result};
+
+pub mod transport;
+pub use transport::WatchTransport;
+
+pub mod response;
+pub use response::Response;
+
+pub mod watch;
+pub use watch::Watch;
+
+pub mod event;
+pub use event::WatchEvent;
+
+pub mod battery;
+
+pub mod raw;
+
+pub mod vendor;
+pub use vendor::VendorInfo;
+
+pub mod pairing;
+pub use pairing::{PairKeyStore, FileKeyStore, MemoryKeyStore};
+
+pub mod reset;
+pub use reset::{HostProfile, ResetConfirmation};
+
+pub mod history;
+pub use history::{HeartRateHistory, StepHistory};
+
+pub mod discover;
+pub use discover::{discover, WatchCandidate};
+
+pub mod datetime;
+pub use datetime::Timestamp;
+
+pub mod registry;
+
 pub mod common;
 
 pub mod general;
@@ -0,0 +1,183 @@
+use super::*;
+use super::common::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use btleplug::api::BDAddr;
+use tokio::time;
+
+// Enough for the user to accept the pairing dialog on the watch
+pub const PAIRING_TIMEOUT: Duration = Duration::from_secs(30);
+const PAIRING_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Unpaired watches report unset key bytes as 0xFF, thus it's never used
+pub fn random_pair_key() -> PairKey {
+    std::array::from_fn(|_| loop {
+        let key_byte: u8 = rand::random();
+        if key_byte != 0xFF {
+            break key_byte;
+        }
+    })
+}
+
+// Pair keys used with each watch, so that every watch gets its own key and keeps it across sessions
+pub trait PairKeyStore {
+    fn load(&self, address: BDAddr) -> Result<Option<PairKey>>;
+
+    fn store(&mut self, address: BDAddr, pair_key: PairKey) -> Result<()>;
+
+    fn remove(&mut self, address: BDAddr) -> Result<()>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MemoryKeyStore {
+    pub keys: BTreeMap<BDAddr, PairKey>
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PairKeyStore for MemoryKeyStore {
+    fn load(&self, address: BDAddr) -> Result<Option<PairKey>> {
+        Ok(self.keys.get(&address).copied())
+    }
+
+    fn store(&mut self, address: BDAddr, pair_key: PairKey) -> Result<()> {
+        self.keys.insert(address, pair_key);
+        Ok(())
+    }
+
+    fn remove(&mut self, address: BDAddr) -> Result<()> {
+        self.keys.remove(&address);
+        Ok(())
+    }
+}
+
+// One "<address> <key as hex>" line per watch, the file (and its parent directories) being created when first storing a key
+#[derive(Clone, Debug)]
+pub struct FileKeyStore {
+    path: PathBuf
+}
+
+fn invalid_line(line: &str) -> Error {
+    Error::KeyStore(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid pair key entry: '{}'", line)))
+}
+
+fn parse_pair_key(raw: &str) -> Option<PairKey> {
+    if (raw.len() != 2 * std::mem::size_of::<PairKey>()) || !raw.is_ascii() {
+        return None;
+    }
+
+    let mut pair_key = PairKey::default();
+    for (i, key_byte) in pair_key.iter_mut().enumerate() {
+        *key_byte = u8::from_str_radix(&raw[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(pair_key)
+}
+
+impl FileKeyStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into()
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // A missing file is just an empty store
+    fn read_keys(&self) -> Result<BTreeMap<BDAddr, PairKey>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(Error::KeyStore(err))
+        };
+
+        let mut keys = BTreeMap::new();
+        for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (address, pair_key) = line.split_once(' ').ok_or_else(|| invalid_line(line))?;
+            let address = address.parse::<BDAddr>().map_err(|_| invalid_line(line))?;
+            let pair_key = parse_pair_key(pair_key.trim()).ok_or_else(|| invalid_line(line))?;
+            keys.insert(address, pair_key);
+        }
+        Ok(keys)
+    }
+
+    fn write_keys(&self, keys: &BTreeMap<BDAddr, PairKey>) -> Result<()> {
+        if let Some(parent_path) = self.path.parent() {
+            fs::create_dir_all(parent_path).map_err(Error::KeyStore)?;
+        }
+
+        let contents: String = keys.iter().map(|(address, pair_key)| format!("{} {}\n", address, pair_key.iter().map(|key_byte| format!("{:02X}", key_byte)).collect::<String>())).collect();
+        fs::write(&self.path, contents).map_err(Error::KeyStore)
+    }
+}
+
+impl PairKeyStore for FileKeyStore {
+    fn load(&self, address: BDAddr) -> Result<Option<PairKey>> {
+        Ok(self.read_keys()?.get(&address).copied())
+    }
+
+    fn store(&mut self, address: BDAddr, pair_key: PairKey) -> Result<()> {
+        let mut keys = self.read_keys()?;
+        keys.insert(address, pair_key);
+        self.write_keys(&keys)
+    }
+
+    fn remove(&mut self, address: BDAddr) -> Result<()> {
+        let mut keys = self.read_keys()?;
+        if keys.remove(&address).is_some() {
+            self.write_keys(&keys)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // Pairs and checks (through the current pair key) that the watch actually accepted it, giving the user PAIRING_TIMEOUT to accept the pairing dialog
+    pub async fn pair_verified(&self, pair_key: PairKey) -> Result<()> {
+        self.pair(pair_key).await?;
+
+        // Unpaired watches report unset key bytes as 0xFF, which is also reported if no current key is ever received
+        let mut cur_pair_key = [0xFF; 4];
+        let wait_paired = async {
+            loop {
+                match self.pair_key().await {
+                    Ok(pair_key_res) => {
+                        cur_pair_key = pair_key_res;
+                        if cur_pair_key == pair_key {
+                            return Ok(());
+                        }
+                    },
+                    // A lost response just means that we don't know yet, so keep asking
+                    Err(Error::Timeout(_)) => {},
+                    Err(err) => return Err(err)
+                }
+                time::sleep(PAIRING_POLL_INTERVAL).await;
+            }
+        };
+
+        match time::timeout(PAIRING_TIMEOUT, wait_paired).await {
+            Ok(res) => res,
+            Err(_) => Err(Error::PairKeyMismatch { expected: pair_key, current: cur_pair_key })
+        }
+    }
+
+    // The watch's stored key is used, or a new random one (only stored once the watch accepts it)
+    pub async fn pair_with_store(&self, address: BDAddr, store: &mut impl PairKeyStore) -> Result<PairKey> {
+        let stored_pair_key = store.load(address)?;
+        let pair_key = stored_pair_key.unwrap_or_else(random_pair_key);
+
+        self.pair_verified(pair_key).await?;
+        if stored_pair_key.is_none() {
+            store.store(address, pair_key)?;
+        }
+        Ok(pair_key)
+    }
+}
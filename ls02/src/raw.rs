@@ -0,0 +1,151 @@
+use super::*;
+use std::pin::Pin;
+use futures::future;
+use futures::stream::{Stream, StreamExt};
+
+// Every characteristic the watch notifies through, mostly meant for researching the ones no known command uses yet
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Channel {
+    General1,
+    General2,
+    Data1,
+    Data2,
+    Data3,
+    Battery
+}
+
+impl Channel {
+    pub const ALL: [Self; 6] = [Self::General1, Self::General2, Self::Data1, Self::Data2, Self::Data3, Self::Battery];
+
+    pub fn notify_char(self) -> Characteristic {
+        match self {
+            Self::General1 => CHAR_GENERAL_N_1,
+            Self::General2 => CHAR_GENERAL_N_2,
+            Self::Data1 => CHAR_DATA1_N,
+            Self::Data2 => CHAR_DATA2_N,
+            Self::Data3 => CHAR_DATA3_N,
+            Self::Battery => CHAR_BATTERY_LEVEL
+        }
+    }
+
+    // The Battery Level characteristic is read-only
+    pub fn write_char(self) -> Option<Characteristic> {
+        match self {
+            Self::General1 => Some(CHAR_GENERAL_RW_1),
+            Self::General2 => Some(CHAR_GENERAL_RW_2),
+            Self::Data1 => Some(CHAR_DATA1_RW),
+            Self::Data2 => Some(CHAR_DATA2_RW),
+            Self::Data3 => Some(CHAR_DATA3_RW),
+            Self::Battery => None
+        }
+    }
+
+    pub fn from_notify_uuid(char_uuid: Uuid) -> Option<Self> {
+        Self::ALL.into_iter().find(|channel| channel.notify_char().uuid == char_uuid)
+    }
+
+    pub fn from_write_uuid(char_uuid: Uuid) -> Option<Self> {
+        Self::ALL.into_iter().find(|channel| channel.write_char().is_some_and(|write_char| write_char.uuid == char_uuid))
+    }
+}
+
+// Arbitrary bytes written to any known writable characteristic, bypassing the typed requests
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RawCommand {
+    pub char: Characteristic,
+    pub bytes: Vec<u8>,
+    pub write_type: WriteType
+}
+
+impl RawCommand {
+    // Written the same way known commands on that characteristic are (with response unless the characteristic only supports writes without response)
+    pub fn new(char: Characteristic, bytes: Vec<u8>) -> Self {
+        let write_type = if char.properties.contains(CharPropFlags::WRITE) {
+            WriteType::WithResponse
+        }
+        else {
+            WriteType::WithoutResponse
+        };
+
+        Self {
+            char,
+            bytes,
+            write_type
+        }
+    }
+
+    pub fn on_channel(channel: Channel, bytes: Vec<u8>) -> Result<Self> {
+        let char = channel.write_char().ok_or_else(|| Error::InvalidArgument(format!("{:?} channel is not writable", channel)))?;
+        Ok(Self::new(char, bytes))
+    }
+
+    pub fn with_write_type(mut self, write_type: WriteType) -> Self {
+        self.write_type = write_type;
+        self
+    }
+
+    pub fn channel(&self) -> Option<Channel> {
+        Channel::from_write_uuid(self.char.uuid)
+    }
+}
+
+pub async fn write(watch: &impl WatchTransport, cmd: &RawCommand) -> Result<()> {
+    write_with_max_size(watch, cmd, max_write_size(watch).await).await
+}
+
+pub async fn write_with_max_size(watch: &impl WatchTransport, cmd: &RawCommand, max_size: usize) -> Result<()> {
+    if cmd.channel().is_none() {
+        return Err(Error::InvalidArgument(format!("{} is not a known writable characteristic", cmd.char.uuid)));
+    }
+
+    if cmd.bytes.len() > max_size {
+        return Err(Error::PayloadTooLarge { len: cmd.bytes.len(), max: max_size });
+    }
+
+    watch.write(&cmd.char, &cmd.bytes, cmd.write_type).await?;
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RawFrame {
+    pub channel: Channel,
+    pub bytes: Vec<u8>
+}
+
+impl RawFrame {
+    pub fn from_notification(value_notif: &ValueNotification) -> Option<Self> {
+        Channel::from_notify_uuid(value_notif.uuid).map(|channel| Self {
+            channel,
+            bytes: value_notif.value.clone()
+        })
+    }
+}
+
+pub type RawFrameStream = Pin<Box<dyn Stream<Item = RawFrame> + Send>>;
+
+pub async fn subscribe_all(watch: &impl WatchTransport) -> Result<()> {
+    for channel in Channel::ALL {
+        watch.subscribe(&channel.notify_char()).await?;
+    }
+
+    Ok(())
+}
+
+// Subscribes to all channels, notifications from unknown characteristics are skipped
+pub async fn frames(watch: &impl WatchTransport) -> Result<RawFrameStream> {
+    subscribe_all(watch).await?;
+
+    let notif_stream = watch.notifications().await?;
+    Ok(Box::pin(notif_stream.filter_map(|value_notif| future::ready(RawFrame::from_notification(&value_notif)))))
+}
+
+impl<T: WatchTransport> Watch<T> {
+    pub async fn send_raw(&self, cmd: &RawCommand) -> Result<()> {
+        write_with_max_size(self.transport(), cmd, self.max_write_size().await).await
+    }
+
+    pub async fn raw_frames(&self) -> Result<RawFrameStream> {
+        frames(self.transport()).await
+    }
+}
@@ -0,0 +1,166 @@
+use super::*;
+use super::common::*;
+use super::general::req::AlertPushRequest;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Direction {
+    // Written by us
+    Request,
+    // Notified by the watch
+    Response
+}
+
+// How the watch reacts to a request
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Reply {
+    // Nothing is sent back (or nothing is known to be)
+    None,
+    // The very same command is sent back, as confirmation
+    Echo,
+    // A short response confirming the request
+    Ack,
+    // A single response with the requested data
+    Data,
+    // Several responses, followed by an end one
+    Stream
+}
+
+#[derive(Clone, Debug)]
+pub struct CommandInfo {
+    // Path of the type implementing it (relative to the crate root), or just a name for commands with no type yet
+    pub name: &'static str,
+    pub direction: Direction,
+    pub char: Characteristic,
+    // Only for requests
+    pub write_type: Option<WriteType>,
+    pub id: CommandId,
+    pub sub_id: Option<u8>,
+    // Full command size, None for variable-size ones
+    pub len: Option<usize>,
+    pub reply: Reply
+}
+
+impl CommandInfo {
+    pub fn is_request(&self) -> bool {
+        self.direction == Direction::Request
+    }
+
+    pub fn is_response(&self) -> bool {
+        self.direction == Direction::Response
+    }
+
+    // Whether the raw command (received/sent through the given characteristic) is of this kind
+    pub fn matches(&self, char_uuid: Uuid, raw: &[u8]) -> bool {
+        (self.char.uuid == char_uuid) && (raw.first() == Some(&(self.id as u8))) && (self.sub_id.is_none() || (self.sub_id == raw.get(1).copied())) && (self.len.is_none() || (self.len == Some(raw.len())))
+    }
+}
+
+macro_rules! request {
+    ($name:expr, $char:expr, $write_type:expr, $id:expr, $sub_id:expr, $len:expr, $reply:expr) => {
+        CommandInfo {
+            name: $name,
+            direction: Direction::Request,
+            char: $char,
+            write_type: Some($write_type),
+            id: $id,
+            sub_id: $sub_id,
+            len: $len,
+            reply: $reply
+        }
+    };
+}
+
+macro_rules! response {
+    ($name:expr, $char:expr, $id:expr, $sub_id:expr, $len:expr) => {
+        CommandInfo {
+            name: $name,
+            direction: Direction::Response,
+            char: $char,
+            write_type: None,
+            id: $id,
+            sub_id: $sub_id,
+            len: $len,
+            reply: Reply::None
+        }
+    };
+}
+
+// Sub-ID-specific commands are listed before the ones with the same ID but no sub-ID
+pub static COMMANDS: &[CommandInfo] = &[
+    request!("general::req::PairRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Pair, Some(PairCommandSubId::Pair as u8), Some(6), Reply::None),
+    request!("general::req::PairKeyRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Pair, Some(PairCommandSubId::CurrentPairKey as u8), Some(2), Reply::Data),
+    request!("general::req::BatteryRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Battery, None, Some(1), Reply::Data),
+    request!("general::req::SetDateTimeRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::DateTime, None, Some(8), Reply::Echo),
+    request!("general::req::FirmwareRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Firmware, None, Some(1), Reply::Data),
+    request!("general::req::ResetRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Reset, None, Some(2), Reply::None),
+    request!("general::req::ConfigDisplayFormatsRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Config, None, Some(3), Reply::Echo),
+    request!("general::req::AlertStartBatchRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Alert, Some(0), None, Reply::Ack),
+    request!("general::req::AlertPushRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Alert, Some(AlertPushRequest::PUSH_IDENT_VALUE), Some(2), Reply::Ack),
+    request!("general::req::AlertNextBatchRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Alert, None, None, Reply::Ack),
+    request!("general::req::SetUserInfoRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::UserInfo, None, Some(19), Reply::None),
+    request!("general::req::SetWeatherTodayRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Weather, Some(WeatherDate::Today as u8), Some(7), Reply::Ack),
+    request!("general::req::SetWeatherFollowingDaysRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::Weather, Some(WeatherDate::FollowingDays as u8), Some(14), Reply::Ack),
+    request!("general::req::RecordedStepsRequest", CHAR_GENERAL_RW_1, WriteType::WithResponse, CommandId::RecordedStepsGeneral, Some(RecordedStepsCommandSubId::Request as u8), Some(3), Reply::Stream),
+
+    request!("data2::req::HeartRateDataRequest", CHAR_DATA2_RW, WriteType::WithoutResponse, CommandId::HeartRate, Some(HeartRateCommandSubId::DataRequest as u8), Some(2), Reply::Stream),
+    request!("data2::req::HeartRateEnableRequest", CHAR_DATA2_RW, WriteType::WithoutResponse, CommandId::HeartRate, Some(HeartRateCommandSubId::Enable as u8), Some(2), Reply::Ack),
+    request!("data2::req::HeartRateDisableRequest", CHAR_DATA2_RW, WriteType::WithoutResponse, CommandId::HeartRate, Some(HeartRateCommandSubId::Disable as u8), Some(2), Reply::Ack),
+    request!("data2::req::RecordedStepsRequest", CHAR_DATA2_RW, WriteType::WithoutResponse, CommandId::RecordedStepsData2, Some(RecordedStepsCommandSubId::Request as u8), Some(3), Reply::Stream),
+
+    response!("general::res::PairKeyResponse", CHAR_GENERAL_N_1, CommandId::Pair, Some(PairCommandSubId::CurrentPairKey as u8), Some(6)),
+    response!("general::res::BatteryResponse", CHAR_GENERAL_N_1, CommandId::Battery, None, Some(2)),
+    response!("general::res::SetDateTimeResponse", CHAR_GENERAL_N_1, CommandId::DateTime, None, Some(8)),
+    response!("general::res::FirmwareResponse", CHAR_GENERAL_N_1, CommandId::Firmware, None, Some(14)),
+    response!("general::res::ConfigDisplayFormatsResponse", CHAR_GENERAL_N_1, CommandId::Config, None, Some(3)),
+    response!("general::res::DevicePulseResponse", CHAR_GENERAL_N_1, CommandId::DevicePulse, None, Some(2)),
+    response!("general::res::SetWeatherResponse", CHAR_GENERAL_N_1, CommandId::Weather, None, Some(2)),
+    // Alert confirmations have no types yet: the push one carries the received message length, the batch one the batch index
+    response!("AlertPushAck", CHAR_GENERAL_N_1, CommandId::Alert, Some(AlertPushRequest::PUSH_IDENT_VALUE), Some(4)),
+    response!("AlertBatchAck", CHAR_GENERAL_N_1, CommandId::Alert, None, Some(2)),
+    response!("general::res::HeartRateMenuDataPeriodicResponse", CHAR_GENERAL_N_1, CommandId::HeartRateMenuGeneral, Some(HeartRateMenuCommandSubId::InMenu as u8), Some(4)),
+    response!("general::res::HeartRateMenuDataLeavingResponse", CHAR_GENERAL_N_1, CommandId::HeartRateMenuGeneral, Some(HeartRateMenuCommandSubId::LeavingMenu as u8), Some(4)),
+    response!("general::res::HeartRateMenuChangeResponse", CHAR_GENERAL_N_1, CommandId::HeartRateMenuGeneral, Some(HeartRateMenuCommandSubId::InMenu as u8), Some(2)),
+    response!("general::res::SilentModeChangeResponse", CHAR_GENERAL_N_1, CommandId::SilentMode, Some(SilentModeCommandSubId::ModeChanged as u8), Some(20)),
+    response!("general::res::SportStatusResponse", CHAR_GENERAL_N_1, CommandId::Sport, None, Some(4)),
+    response!("general::res::RecordedStepsEndResponse", CHAR_GENERAL_N_1, CommandId::RecordedStepsGeneral, Some(RecordedStepsCommandSubId::End as u8), Some(3)),
+    response!("general::res::RecordedStepsEntryResponse", CHAR_GENERAL_N_1, CommandId::RecordedStepsGeneral, None, Some(18)),
+
+    response!("data2::res::HeartRateDataTodayResponse", CHAR_DATA2_N, CommandId::HeartRate, Some(HeartRateCommandSubId::DataToday as u8), Some(11)),
+    response!("data2::res::HeartRateDataEndResponse", CHAR_DATA2_N, CommandId::HeartRate, Some(HeartRateCommandSubId::DataEnd as u8), Some(3)),
+    response!("data2::res::HeartRateDataPeriodicResponse", CHAR_DATA2_N, CommandId::HeartRate, Some(HeartRateCommandSubId::DataPeriodic as u8), Some(9)),
+    response!("data2::res::HeartRateEnableResponse", CHAR_DATA2_N, CommandId::HeartRate, Some(HeartRateCommandSubId::Enable as u8), Some(2)),
+    response!("data2::res::HeartRateDisableResponse", CHAR_DATA2_N, CommandId::HeartRate, Some(HeartRateCommandSubId::Disable as u8), Some(2)),
+    // No sub-ID here, the second byte is already part of the date
+    response!("data2::res::HeartRateDataDayHourEntryResponse", CHAR_DATA2_N, CommandId::HeartRate, None, Some(18)),
+    response!("data2::res::StepsResponse", CHAR_DATA2_N, CommandId::Steps, None, Some(18)),
+    response!("data2::res::HeartRateMenuPeriodicResponse", CHAR_DATA2_N, CommandId::HeartRateMenuData, Some(HeartRateMenuCommandSubId::InMenu as u8), Some(4)),
+    response!("data2::res::HeartRateMenuMoveDownResponse", CHAR_DATA2_N, CommandId::HeartRateMenuData, Some(HeartRateMenuCommandSubId::InMenu as u8), Some(2)),
+    response!("data2::res::HeartRateDataTodayUnk2Response", CHAR_DATA2_N, CommandId::HeartRateUnk2, Some(HeartRateCommandSubId::DataToday as u8), Some(11)),
+    response!("data2::res::HeartRateDataPeriodicUnk2Response", CHAR_DATA2_N, CommandId::HeartRateUnk2, Some(HeartRateCommandSubId::DataPeriodic as u8), Some(9)),
+    response!("data2::res::StepsUnk2Response", CHAR_DATA2_N, CommandId::StepsUnk2, None, Some(18)),
+    response!("data2::res::DevicePulseUnk2Response", CHAR_DATA2_N, CommandId::DevicePulseUnk2, None, Some(2)),
+    response!("data2::res::RecordedStepsEndResponse", CHAR_DATA2_N, CommandId::RecordedStepsData2, Some(RecordedStepsCommandSubId::End as u8), Some(3)),
+    response!("data2::res::RecordedStepsEntryResponse", CHAR_DATA2_N, CommandId::RecordedStepsData2, None, Some(18))
+];
+
+pub fn requests() -> impl Iterator<Item = &'static CommandInfo> {
+    COMMANDS.iter().filter(|cmd| cmd.is_request())
+}
+
+pub fn responses() -> impl Iterator<Item = &'static CommandInfo> {
+    COMMANDS.iter().filter(|cmd| cmd.is_response())
+}
+
+pub fn find(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|cmd| cmd.name == name)
+}
+
+// First command matching the raw data (sent or received through the given characteristic)
+pub fn identify(char_uuid: Uuid, raw: &[u8]) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|cmd| cmd.matches(char_uuid, raw))
+}
+
+// Whether any command (through any characteristic) uses this ID
+pub fn is_known_id(id: u8) -> bool {
+    COMMANDS.iter().any(|cmd| cmd.id as u8 == id)
+}
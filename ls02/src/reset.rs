@@ -0,0 +1,139 @@
+use super::*;
+use super::common::*;
+use std::time::Duration;
+use tokio::time;
+
+pub const RESET_DISCONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const RESET_RECONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+const RESET_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Settings the watch forgets when reset, restored from the host after rebooting
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostProfile {
+    // The date/time is taken from the host's clock, shifted by this offset (seconds east of UTC)
+    pub utc_offset_secs: i32,
+    pub distance_unit: DistanceUnit,
+    pub time_fmt: TimeFormat,
+    pub user_info: Option<general::req::SetUserInfoRequest>
+}
+
+impl HostProfile {
+    pub const fn new(utc_offset_secs: i32, distance_unit: DistanceUnit, time_fmt: TimeFormat) -> Self {
+        Self {
+            utc_offset_secs,
+            distance_unit,
+            time_fmt,
+            user_info: None
+        }
+    }
+
+    pub fn with_user_info(mut self, user_info: general::req::SetUserInfoRequest) -> Self {
+        self.user_info = Some(user_info);
+        self
+    }
+}
+
+// Required to reset: it states both the operation and the watch's current pair key, so that neither a different operation nor a different watch can be reset by mistake
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResetConfirmation {
+    pub sub_id: ResetCommandSubId,
+    // After rebooting, the watch is re-paired with this same key
+    pub pair_key: PairKey,
+    // Nothing else is restored after rebooting if not present
+    pub profile: Option<HostProfile>
+}
+
+impl ResetConfirmation {
+    pub const fn reboot(pair_key: PairKey) -> Self {
+        Self {
+            sub_id: ResetCommandSubId::ResetAndReboot,
+            pair_key,
+            profile: None
+        }
+    }
+
+    pub const fn power_off(pair_key: PairKey) -> Self {
+        Self {
+            sub_id: ResetCommandSubId::ResetAndPowerOff,
+            pair_key,
+            profile: None
+        }
+    }
+
+    pub fn with_profile(mut self, profile: HostProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    // Erases the watch, waits for it to disconnect and (when rebooting) reconnects, re-pairs and restores the confirmation's profile
+    pub async fn reset(&self, sub_id: ResetCommandSubId, confirm: ResetConfirmation) -> Result<()> {
+        if confirm.sub_id != sub_id {
+            return Err(Error::InvalidArgument(format!("Reset confirmation is for {:?}, not {:?}", confirm.sub_id, sub_id)));
+        }
+
+        let cur_pair_key = self.pair_key().await?;
+        if cur_pair_key != confirm.pair_key {
+            return Err(Error::PairKeyMismatch { expected: confirm.pair_key, current: cur_pair_key });
+        }
+
+        // The watch may already be gone by the time the write is acknowledged
+        if let Err(err) = self.send(general::req::ResetRequest::new(sub_id)).await {
+            if self.transport().connected().await? {
+                return Err(err);
+            }
+        }
+        self.wait_disconnected(RESET_DISCONNECT_TIMEOUT).await?;
+
+        match sub_id {
+            ResetCommandSubId::ResetAndPowerOff => Ok(()),
+            ResetCommandSubId::ResetAndReboot => {
+                self.wait_reconnected(RESET_RECONNECT_TIMEOUT).await?;
+
+                // The reset watch shows the pairing dialog again, and won't answer anything else until the user accepts it
+                self.pair_verified(confirm.pair_key).await?;
+
+                match confirm.profile {
+                    Some(profile) => self.restore_profile(&profile).await,
+                    None => Ok(())
+                }
+            }
+        }
+    }
+
+    pub async fn restore_profile(&self, profile: &HostProfile) -> Result<()> {
+        self.set_datetime_request(general::req::SetDateTimeRequest::now(profile.utc_offset_secs)?).await?;
+        self.set_display_formats(profile.distance_unit, profile.time_fmt).await?;
+        if let Some(user_info) = &profile.user_info {
+            self.set_user_info(user_info.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn wait_disconnected(&self, timeout: Duration) -> Result<()> {
+        let wait_disconnect = async {
+            while self.transport().connected().await? {
+                time::sleep(RESET_POLL_INTERVAL).await;
+            }
+            Ok(())
+        };
+
+        time::timeout(timeout, wait_disconnect).await.map_err(|_| Error::Timeout(timeout))?
+    }
+
+    // Connection attempts are retried (while rebooting the watch isn't available yet)
+    async fn wait_reconnected(&self, timeout: Duration) -> Result<()> {
+        let wait_connect = async {
+            while self.transport().reconnect().await.is_err() {
+                time::sleep(RESET_POLL_INTERVAL).await;
+            }
+        };
+
+        time::timeout(timeout, wait_connect).await.map_err(|_| Error::Timeout(timeout))?;
+        Self::subscribe(self.transport()).await
+    }
+}
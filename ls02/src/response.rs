@@ -0,0 +1,140 @@
+use super::*;
+use super::common::*;
+use super::registry::{self, CommandInfo};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Response {
+    PairKey(general::res::PairKeyResponse),
+    Battery(general::res::BatteryResponse),
+    SetDateTime(general::res::SetDateTimeResponse),
+    Firmware(general::res::FirmwareResponse),
+    ConfigDisplayFormats(general::res::ConfigDisplayFormatsResponse),
+    DevicePulse(general::res::DevicePulseResponse),
+    SetWeather(general::res::SetWeatherResponse),
+    HeartRateMenuDataPeriodic(general::res::HeartRateMenuDataPeriodicResponse),
+    HeartRateMenuDataLeaving(general::res::HeartRateMenuDataLeavingResponse),
+    HeartRateMenuChange(general::res::HeartRateMenuChangeResponse),
+    SilentModeChange(general::res::SilentModeChangeResponse),
+    SportStatus(general::res::SportStatusResponse),
+    RecordedStepsEntryGeneral(general::res::RecordedStepsEntryResponse),
+    RecordedStepsEndGeneral(general::res::RecordedStepsEndResponse),
+
+    HeartRateDataToday(data2::res::HeartRateDataTodayResponse),
+    HeartRateDataEnd(data2::res::HeartRateDataEndResponse),
+    HeartRateDataDayHourEntry(data2::res::HeartRateDataDayHourEntryResponse),
+    HeartRateDataPeriodic(data2::res::HeartRateDataPeriodicResponse),
+    HeartRateEnable(data2::res::HeartRateEnableResponse),
+    HeartRateDisable(data2::res::HeartRateDisableResponse),
+    Steps(data2::res::StepsResponse),
+    HeartRateMenuPeriodic(data2::res::HeartRateMenuPeriodicResponse),
+    HeartRateMenuMoveDown(data2::res::HeartRateMenuMoveDownResponse),
+    HeartRateDataTodayUnk2(data2::res::HeartRateDataTodayUnk2Response),
+    StepsUnk2(data2::res::StepsUnk2Response),
+    HeartRateDataPeriodicUnk2(data2::res::HeartRateDataPeriodicUnk2Response),
+    DevicePulseUnk2(data2::res::DevicePulseUnk2Response),
+    RecordedStepsEntryData2(data2::res::RecordedStepsEntryResponse),
+    RecordedStepsEndData2(data2::res::RecordedStepsEndResponse),
+
+    Unknown { char: Uuid, bytes: Vec<u8> }
+}
+
+// Decoded response and trailing bytes
+type DecodeFn = fn(&[u8], ParseMode) -> Option<(Response, Vec<u8>)>;
+
+// How each response is told apart (characteristic, ID, sub-ID and size) comes from the registry, only decoding is done here
+struct ResponseKind {
+    // Registry name of the type
+    name: &'static str,
+    decode: DecodeFn
+}
+
+macro_rules! response_kind {
+    ($module:ident :: $dir:ident :: $t:ident => $variant:ident) => {
+        ResponseKind {
+            name: concat!(stringify!($module), "::", stringify!($dir), "::", stringify!($t)),
+            decode: |raw, mode| <$module::$dir::$t>::parse_with(raw, mode).ok().map(|(res, trailing)| (Response::$variant(res), trailing))
+        }
+    };
+}
+
+static RESPONSE_KINDS: &[ResponseKind] = &[
+    response_kind!(general::res::PairKeyResponse => PairKey),
+    response_kind!(general::res::BatteryResponse => Battery),
+    response_kind!(general::res::SetDateTimeResponse => SetDateTime),
+    response_kind!(general::res::FirmwareResponse => Firmware),
+    response_kind!(general::res::ConfigDisplayFormatsResponse => ConfigDisplayFormats),
+    response_kind!(general::res::DevicePulseResponse => DevicePulse),
+    response_kind!(general::res::SetWeatherResponse => SetWeather),
+    response_kind!(general::res::HeartRateMenuDataPeriodicResponse => HeartRateMenuDataPeriodic),
+    response_kind!(general::res::HeartRateMenuDataLeavingResponse => HeartRateMenuDataLeaving),
+    response_kind!(general::res::HeartRateMenuChangeResponse => HeartRateMenuChange),
+    response_kind!(general::res::SilentModeChangeResponse => SilentModeChange),
+    response_kind!(general::res::SportStatusResponse => SportStatus),
+    response_kind!(general::res::RecordedStepsEndResponse => RecordedStepsEndGeneral),
+    response_kind!(general::res::RecordedStepsEntryResponse => RecordedStepsEntryGeneral),
+
+    response_kind!(data2::res::HeartRateDataTodayResponse => HeartRateDataToday),
+    response_kind!(data2::res::HeartRateDataEndResponse => HeartRateDataEnd),
+    response_kind!(data2::res::HeartRateDataPeriodicResponse => HeartRateDataPeriodic),
+    response_kind!(data2::res::HeartRateEnableResponse => HeartRateEnable),
+    response_kind!(data2::res::HeartRateDisableResponse => HeartRateDisable),
+    response_kind!(data2::res::HeartRateDataDayHourEntryResponse => HeartRateDataDayHourEntry),
+    response_kind!(data2::res::StepsResponse => Steps),
+    response_kind!(data2::res::HeartRateMenuPeriodicResponse => HeartRateMenuPeriodic),
+    response_kind!(data2::res::HeartRateMenuMoveDownResponse => HeartRateMenuMoveDown),
+    response_kind!(data2::res::HeartRateDataTodayUnk2Response => HeartRateDataTodayUnk2),
+    response_kind!(data2::res::HeartRateDataPeriodicUnk2Response => HeartRateDataPeriodicUnk2),
+    response_kind!(data2::res::StepsUnk2Response => StepsUnk2),
+    response_kind!(data2::res::DevicePulseUnk2Response => DevicePulseUnk2),
+    response_kind!(data2::res::RecordedStepsEndResponse => RecordedStepsEndData2),
+    response_kind!(data2::res::RecordedStepsEntryResponse => RecordedStepsEntryData2)
+];
+
+impl Response {
+    // Also returns the bytes following the response, if any (only in lenient mode, where responses longer than expected are still decoded)
+    pub fn decode_with(char_uuid: Uuid, raw: &[u8], mode: ParseMode) -> (Self, Vec<u8>) {
+        let unknown = || (Response::Unknown { char: char_uuid, bytes: raw.to_vec() }, Vec::new());
+
+        let id = match raw.first().and_then(|id| Cursor::new([*id]).read_le::<CommandId>().ok()) {
+            Some(id) => id,
+            None => return unknown()
+        };
+        let sub_id = raw.get(1).copied();
+
+        // Sub-ID-specific commands are listed first in the registry, so the first match is the right one
+        let info_matches = |info: &CommandInfo| {
+            (info.char.uuid == char_uuid) && (info.id == id) && (info.sub_id.is_none() || (info.sub_id == sub_id))
+        };
+        let mut info = registry::responses().find(|info| info_matches(info) && (info.len == Some(raw.len())));
+        if info.is_none() && (mode == ParseMode::Lenient) {
+            // Same-ID-and-sub-ID commands are told apart by their size, so the longest one fitting is chosen
+            info = registry::responses().filter(|info| info_matches(info) && info.len.is_some_and(|len| len < raw.len())).max_by_key(|info| info.len);
+        }
+
+        // Some responses (like alert confirmations) are known but have no type yet
+        match info.and_then(|info| RESPONSE_KINDS.iter().find(|kind| kind.name == info.name)) {
+            Some(kind) => (kind.decode)(raw, mode).unwrap_or_else(unknown),
+            None => unknown()
+        }
+    }
+
+    #[inline]
+    pub fn decode(char_uuid: Uuid, raw: &[u8]) -> Self {
+        Self::decode_with(char_uuid, raw, ParseMode::Lenient).0
+    }
+
+    #[inline]
+    pub fn decode_strict(char_uuid: Uuid, raw: &[u8]) -> Self {
+        Self::decode_with(char_uuid, raw, ParseMode::Strict).0
+    }
+
+    #[inline]
+    pub fn from_notification(value_notif: &ValueNotification) -> Self {
+        Self::decode(value_notif.uuid, &value_notif.value)
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Response::Unknown { .. })
+    }
+}
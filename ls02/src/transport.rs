@@ -0,0 +1,68 @@
+use super::*;
+use std::pin::Pin;
+use async_trait::async_trait;
+use futures::stream::Stream;
+
+pub type NotificationStream = Pin<Box<dyn Stream<Item = ValueNotification> + Send>>;
+
+// A write can carry up to the ATT MTU minus the ATT header (opcode + handle)
+pub const ATT_HEADER_SIZE: usize = 3;
+
+// Anything able to carry LS02 commands: a real BLE peripheral, an in-memory simulator, a recorded session, a remote bridge...
+// Each call to notifications() must return a new stream that yields every notification received from that point on (for all subscribed characteristics), same as btleplug does
+#[async_trait]
+pub trait WatchTransport: Send + Sync {
+    async fn write(&self, characteristic: &Characteristic, data: &[u8], write_type: WriteType) -> btleplug::Result<()>;
+
+    async fn read(&self, characteristic: &Characteristic) -> btleplug::Result<Vec<u8>>;
+
+    async fn subscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()>;
+
+    async fn unsubscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()>;
+
+    async fn notifications(&self) -> btleplug::Result<NotificationStream>;
+
+    async fn connected(&self) -> btleplug::Result<bool>;
+
+    // Used after the watch reboots, services must be usable once this returns
+    async fn reconnect(&self) -> btleplug::Result<()>;
+
+    // Negotiated ATT MTU, if known
+    async fn mtu(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[async_trait]
+impl WatchTransport for Peripheral {
+    async fn write(&self, characteristic: &Characteristic, data: &[u8], write_type: WriteType) -> btleplug::Result<()> {
+        btleplug::api::Peripheral::write(self, characteristic, data, write_type).await
+    }
+
+    async fn read(&self, characteristic: &Characteristic) -> btleplug::Result<Vec<u8>> {
+        btleplug::api::Peripheral::read(self, characteristic).await
+    }
+
+    async fn subscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()> {
+        btleplug::api::Peripheral::subscribe(self, characteristic).await
+    }
+
+    async fn unsubscribe(&self, characteristic: &Characteristic) -> btleplug::Result<()> {
+        btleplug::api::Peripheral::unsubscribe(self, characteristic).await
+    }
+
+    async fn notifications(&self) -> btleplug::Result<NotificationStream> {
+        btleplug::api::Peripheral::notifications(self).await
+    }
+
+    async fn connected(&self) -> btleplug::Result<bool> {
+        btleplug::api::Peripheral::is_connected(self).await
+    }
+
+    async fn reconnect(&self) -> btleplug::Result<()> {
+        btleplug::api::Peripheral::connect(self).await?;
+        btleplug::api::Peripheral::discover_services(self).await
+    }
+
+    // btleplug doesn't expose the negotiated MTU (yet), so the (safe) MAX_WRITE_SIZE is used unless overridden with Watch::with_mtu()
+}
@@ -0,0 +1,107 @@
+use super::*;
+use std::fmt;
+
+// What each characteristic contains is still unknown, thus values are decoded by their looks
+pub const VENDOR_CHARACTERISTICS: &[Characteristic] = &[
+    CHAR_VENDOR_FFD2,
+    CHAR_VENDOR_FFD3,
+    CHAR_VENDOR_FFD4,
+    CHAR_VENDOR_FFD5,
+    CHAR_VENDOR_FFE0,
+    CHAR_VENDOR_FFF1
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VendorValue {
+    // Revision strings and such (trailing NULs removed)
+    Text(String),
+    // MAC-like identifiers, kept in the order they were read (BLE addresses are usually little-endian)
+    Address([u8; 6]),
+    // Little-endian
+    Number(u32),
+    Bytes(Vec<u8>)
+}
+
+impl VendorValue {
+    pub fn decode(raw: &[u8]) -> Self {
+        let text_len = raw.iter().rposition(|&b| b != 0).map_or(0, |last_idx| last_idx + 1);
+        let text = &raw[..text_len];
+        if (text.len() >= 2) && text.iter().all(|&b| b.is_ascii_graphic() || (b == b' ')) {
+            return VendorValue::Text(String::from_utf8_lossy(text).into_owned());
+        }
+
+        match raw.len() {
+            6 => VendorValue::Address(raw.try_into().unwrap()),
+            1 | 2 | 4 => {
+                let mut number_data = [0u8; 4];
+                number_data[..raw.len()].copy_from_slice(raw);
+                VendorValue::Number(u32::from_le_bytes(number_data))
+            },
+            _ => VendorValue::Bytes(raw.to_vec())
+        }
+    }
+}
+
+impl fmt::Display for VendorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VendorValue::Text(text) => write!(f, "\"{}\"", text),
+            VendorValue::Address(addr) => write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X} (reversed: {:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X})", addr[0], addr[1], addr[2], addr[3], addr[4], addr[5], addr[5], addr[4], addr[3], addr[2], addr[1], addr[0]),
+            VendorValue::Number(number) => write!(f, "{} ({:#X})", number, number),
+            VendorValue::Bytes(bytes) => write!(f, "{:02X?}", bytes)
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VendorField {
+    pub char_uuid: Uuid,
+    // None if the characteristic couldn't be read (missing, requires authentication...)
+    pub raw: Option<Vec<u8>>
+}
+
+impl VendorField {
+    pub fn value(&self) -> Option<VendorValue> {
+        self.raw.as_deref().map(VendorValue::decode)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VendorInfo {
+    pub fields: Vec<VendorField>
+}
+
+impl VendorInfo {
+    pub fn get(&self, char: &Characteristic) -> Option<&VendorField> {
+        self.fields.iter().find(|field| field.char_uuid == char.uuid)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.iter().all(|field| field.raw.is_none())
+    }
+}
+
+// Only reads are performed, so this is safe to run against any watch
+// Unreadable characteristics are just reported as such, thus this never fails
+pub async fn probe(watch: &impl WatchTransport) -> VendorInfo {
+    let mut fields = Vec::with_capacity(VENDOR_CHARACTERISTICS.len());
+    for char in VENDOR_CHARACTERISTICS {
+        fields.push(VendorField {
+            char_uuid: char.uuid,
+            raw: watch.read(char).await.ok()
+        });
+    }
+
+    VendorInfo {
+        fields
+    }
+}
+
+impl<T: WatchTransport> Watch<T> {
+    pub async fn vendor_info(&self) -> VendorInfo {
+        probe(self.transport()).await
+    }
+}
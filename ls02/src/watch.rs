@@ -0,0 +1,195 @@
+use super::*;
+use super::common::*;
+use std::time::Duration;
+use futures::stream::StreamExt;
+use tokio::time;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Higher-level client: sends a request and waits for (and checks) the matching response, instead of having to watch notifications separately
+#[derive(Clone)]
+pub struct Watch<T: WatchTransport> {
+    transport: T,
+    timeout: Duration,
+    mtu: Option<usize>
+}
+
+impl<T: WatchTransport> Watch<T> {
+    pub async fn new(transport: T) -> Result<Self> {
+        Self::subscribe(&transport).await?;
+
+        Ok(Self {
+            transport,
+            timeout: DEFAULT_TIMEOUT,
+            mtu: None
+        })
+    }
+
+    // Subscriptions are lost whenever the watch disconnects
+    pub(crate) async fn subscribe(transport: &T) -> Result<()> {
+        transport.subscribe(&CHAR_GENERAL_N_1).await?;
+        transport.subscribe(&CHAR_DATA2_N).await?;
+        Ok(())
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    // Overrides the transport's MTU, for instance since btleplug peripherals never know the negotiated one
+    // The platform must have actually negotiated (at least) this MTU, otherwise bigger writes are rejected
+    pub fn with_mtu(mut self, mtu: usize) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    pub fn set_mtu(&mut self, mtu: Option<usize>) {
+        self.mtu = mtu;
+    }
+
+    pub async fn max_write_size(&self) -> usize {
+        match self.mtu {
+            Some(mtu) => mtu.saturating_sub(transport::ATT_HEADER_SIZE),
+            None => max_write_size(&self.transport).await
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    // For requests the watch doesn't respond to
+    pub async fn send<C: CommandRequest + Send>(&self, cmd: C) -> Result<()> where for<'a> <C as BinWrite>::Args<'a>: Default {
+        write_with_max_size(&self.transport, cmd, self.max_write_size().await).await
+    }
+
+    // Fragmented depending on the MTU (see max_write_size())
+    pub async fn send_multi_frame<M: MultiFrameRequest + Sync>(&self, req: &M) -> Result<()> {
+        write_multi_frame_with_max_size(&self.transport, req, self.max_write_size().await).await
+    }
+
+    // The notification stream is obtained before writing, so that a quick response can't be missed
+    // Other notifications received meanwhile (periodic heart rate, steps...) are skipped
+    pub async fn request<C: CommandRequest + Send, R>(&self, cmd: C, mut filter: impl FnMut(Response) -> Option<R> + Send) -> Result<R> where for<'a> <C as BinWrite>::Args<'a>: Default {
+        let mut notif_stream = self.transport.notifications().await?;
+        self.send(cmd).await?;
+
+        let wait_response = async {
+            while let Some(value_notif) = notif_stream.next().await {
+                if let Some(res) = filter(Response::from_notification(&value_notif)) {
+                    return Some(res);
+                }
+            }
+            None
+        };
+
+        match time::timeout(self.timeout, wait_response).await {
+            Ok(Some(res)) => Ok(res),
+            Ok(None) => Err(Error::NotificationStreamClosed),
+            Err(_) => Err(Error::Timeout(self.timeout))
+        }
+    }
+
+    pub async fn battery(&self) -> Result<u8> {
+        self.request(general::req::BatteryRequest::new(), |res| match res {
+            Response::Battery(battery_res) => Some(battery_res.battery_percentage),
+            _ => None
+        }).await
+    }
+
+    pub async fn firmware(&self) -> Result<String> {
+        let name = self.request(general::req::FirmwareRequest::new(), |res| match res {
+            Response::Firmware(firmware_res) => Some(firmware_res.name),
+            _ => None
+        }).await?;
+
+        Ok(String::from_utf8_lossy(&name).trim_end_matches('\0').to_string())
+    }
+
+    // The watch may show the pairing prompt to the user, no response is expected here
+    pub async fn pair(&self, pair_key: PairKey) -> Result<()> {
+        self.send(general::req::PairRequest::new(pair_key)).await
+    }
+
+    pub async fn pair_key(&self) -> Result<PairKey> {
+        self.request(general::req::PairKeyRequest::new(), |res| match res {
+            Response::PairKey(pair_key_res) => Some(pair_key_res.cur_pair_key),
+            _ => None
+        }).await
+    }
+
+    pub async fn set_datetime(&self, date: Date, hour: u8, min: u8, sec: u8) -> Result<()> {
+        self.set_datetime_request(general::req::SetDateTimeRequest::try_new(date, hour, min, sec)?).await
+    }
+
+    // For instance, with the chrono feature: SetDateTimeRequest::try_from(Local::now().naive_local())
+    pub async fn set_datetime_request(&self, req: general::req::SetDateTimeRequest) -> Result<()> {
+        let echo = self.request(req, |res| match res {
+            Response::SetDateTime(datetime_res) => Some(datetime_res),
+            _ => None
+        }).await?;
+
+        if echo.timestamp() != req.timestamp() {
+            return Err(Error::UnexpectedResponse(Response::SetDateTime(echo)));
+        }
+        Ok(())
+    }
+
+    pub async fn set_display_formats(&self, distance_unit: DistanceUnit, time_fmt: TimeFormat) -> Result<()> {
+        let echo = self.request(general::req::ConfigDisplayFormatsRequest::new(distance_unit, time_fmt), |res| match res {
+            Response::ConfigDisplayFormats(config_res) => Some(config_res),
+            _ => None
+        }).await?;
+
+        if (echo.distance_unit != distance_unit) || (echo.time_fmt != time_fmt) {
+            return Err(Error::UnexpectedResponse(Response::ConfigDisplayFormats(echo)));
+        }
+        Ok(())
+    }
+
+    pub async fn set_weather_today(&self, weather_type: WeatherType, cur_temperature: i8, max_temperature: i8, min_temperature: i8) -> Result<()> {
+        self.set_weather_today_request(general::req::SetWeatherTodayRequest::new(weather_type, cur_temperature, max_temperature, min_temperature)).await
+    }
+
+    pub async fn set_weather_today_request(&self, req: general::req::SetWeatherTodayRequest) -> Result<()> {
+        self.set_weather(req, WeatherDate::Today).await
+    }
+
+    pub async fn set_weather_following_days(&self, req: general::req::SetWeatherFollowingDaysRequest) -> Result<()> {
+        self.set_weather(req, WeatherDate::FollowingDays).await
+    }
+
+    async fn set_weather<C: CommandRequest + Send>(&self, req: C, weather_date: WeatherDate) -> Result<()> where for<'a> <C as BinWrite>::Args<'a>: Default {
+        let echo = self.request(req, |res| match res {
+            Response::SetWeather(weather_res) => Some(weather_res),
+            _ => None
+        }).await?;
+
+        if echo.weather_date != weather_date {
+            return Err(Error::UnexpectedResponse(Response::SetWeather(echo)));
+        }
+        Ok(())
+    }
+
+    // The watch doesn't respond to this one
+    pub async fn set_user_info(&self, req: general::req::SetUserInfoRequest) -> Result<()> {
+        self.send(req).await
+    }
+
+    pub async fn send_alert(&self, alert_type: AlertType, text: String) -> Result<()> {
+        self.send_multi_frame(&general::req::AlertRequest::new(alert_type, &text)).await
+    }
+
+    pub async fn send_alert_message(&self, msg: &general::alert::AlertMessage) -> Result<()> {
+        self.send_multi_frame(&msg.build()?).await
+    }
+}
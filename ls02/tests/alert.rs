@@ -0,0 +1,120 @@
+// Alert texts must never exceed the requested length (whatever the truncation policy) and frames must never split a UTF-16 surrogate pair
+
+use std::ops::Range;
+use btleplug::api::{Characteristic, WriteType};
+use ls02::MultiFrameRequest;
+use ls02::common::*;
+use ls02::general::alert::{AlertMessage, TruncationPolicy};
+use ls02::general::req::AlertRequest;
+
+fn units(frame: &[u8], header_size: usize) -> Vec<u16> {
+    frame[header_size..].chunks(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]])).collect()
+}
+
+#[test]
+fn surrogate_pairs_are_never_split() {
+    // Start frames fit 22 units at MAX_WRITE_SIZE, the emoji taking units 21 and 22
+    let text = format!("{}\u{1F600}b", "a".repeat(21));
+    let frames = AlertRequest::new(AlertType::Message, &text).fragment(ls02::MAX_WRITE_SIZE).unwrap();
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(units(&frames[0], 4), vec![u16::from(b'a'); 21]);
+    assert_eq!(units(&frames[1], 2), [0xD83D, 0xDE00, u16::from(b'b')]);
+}
+
+#[test]
+fn truncation_limits() {
+    let msg = |body: &str, truncation: TruncationPolicy| AlertMessage::new(AlertType::Message, body).with_max_len(10).with_truncation(truncation);
+    let exact = "0123456789";
+    let overlong = "0123456789A";
+
+    for truncation in [TruncationPolicy::Error, TruncationPolicy::Truncate, TruncationPolicy::Ellipsis] {
+        assert_eq!(msg(exact, truncation).text().unwrap(), exact);
+    }
+
+    assert!(matches!(msg(overlong, TruncationPolicy::Error).text(), Err(ls02::Error::PayloadTooLarge { len: 22, max: 20 })));
+    assert_eq!(msg(overlong, TruncationPolicy::Truncate).text().unwrap(), exact);
+    assert_eq!(msg(overlong, TruncationPolicy::Ellipsis).text().unwrap(), "012345678\u{2026}");
+    assert_eq!(msg(overlong, TruncationPolicy::Ellipsis).with_transliteration(true).text().unwrap(), "0123456...");
+
+    // Surrogate pairs not fitting are left out whole
+    assert_eq!(msg("012345678\u{1F600}", TruncationPolicy::Truncate).text().unwrap(), "012345678");
+
+    assert!(matches!(msg(exact, TruncationPolicy::Truncate).with_max_len(AlertRequest::MAX_TEXT_LEN + 1).text(), Err(ls02::Error::InvalidArgument(_))));
+}
+
+#[test]
+fn ellipsis_longer_than_max_len() {
+    let msg = AlertMessage::new(AlertType::Message, "0123456789").with_truncation(TruncationPolicy::Ellipsis).with_transliteration(true);
+
+    assert_eq!(msg.clone().with_max_len(3).text().unwrap(), "...");
+    assert_eq!(msg.clone().with_max_len(2).text().unwrap(), "..");
+    assert_eq!(msg.clone().with_max_len(0).text().unwrap(), "");
+    assert_eq!(msg.with_transliteration(false).with_max_len(1).text().unwrap(), "\u{2026}");
+}
+
+#[test]
+fn title_and_body() {
+    let msg = AlertMessage::new(AlertType::WhatsApp, "See you at 8").with_title("Alice");
+    assert_eq!(msg.full_text(), format!("Alice{}See you at 8", AlertMessage::TITLE_SEPARATOR));
+
+    // The title counts towards the length too
+    let msg = msg.with_max_len(8).with_truncation(TruncationPolicy::Truncate);
+    assert_eq!(msg.text().unwrap(), "Alice\nSe");
+}
+
+#[test]
+fn transliteration() {
+    let body = "\u{201C}Hi\u{201D} \u{2014} it\u{2019}s\u{2026}\u{00A0}\u{1F600}\u{200B}";
+    let msg = AlertMessage::new(AlertType::Message, body);
+
+    assert_eq!(msg.full_text(), body);
+    assert_eq!(msg.with_transliteration(true).full_text(), "\"Hi\" - it's... ?");
+}
+
+#[test]
+fn preview_frames() {
+    let frames = AlertMessage::new(AlertType::Message, "Hi").with_title("T").preview(ls02::MAX_WRITE_SIZE).unwrap();
+
+    assert_eq!(frames, [
+        vec![0x0F, 0x00, u8::from(AlertType::Message), 0x08, 0x00, b'T', 0x00, b'\n', 0x00, b'H', 0x00, b'i'],
+        vec![0x0F, 0xFD]
+    ]);
+}
+
+// One byte units, one unit per frame
+struct ByteRequest(usize);
+
+impl MultiFrameRequest for ByteRequest {
+    const WRITE_TYPE: WriteType = WriteType::WithResponse;
+    const CHAR: Characteristic = ls02::CHAR_GENERAL_RW_1;
+    const UNIT_SIZE: usize = 1;
+
+    fn unit_count(&self) -> usize {
+        self.0
+    }
+
+    fn start_frame(&self, units: Range<usize>) -> ls02::Result<Vec<u8>> {
+        Ok([vec![0xAA, 0x00], vec![0; units.len()]].concat())
+    }
+
+    fn next_frame(&self, frame_idx: u8, units: Range<usize>) -> ls02::Result<Vec<u8>> {
+        Ok([vec![0xAA, frame_idx], vec![0; units.len()]].concat())
+    }
+
+    fn push_frame(&self) -> ls02::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
+
+#[test]
+fn frame_idx_overflow() {
+    // The start frame and 252 next ones, the last being 0xFC (0xFD would look like a push frame)
+    let frames = ByteRequest(253).fragment(3).unwrap();
+    assert_eq!(frames.len(), 253);
+    assert_eq!(frames.last().unwrap(), &[0xAA, ByteRequest::MAX_FRAME_IDX, 0]);
+    assert_eq!(ByteRequest::MAX_FRAME_IDX, 0xFC);
+    assert!(frames.iter().all(|frame| frame[1] != ls02::general::req::AlertPushRequest::PUSH_IDENT_VALUE));
+
+    assert!(matches!(ByteRequest(254).fragment(3), Err(ls02::Error::PayloadTooLarge { len: 254, max: 253 })));
+}
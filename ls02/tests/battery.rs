@@ -0,0 +1,51 @@
+// Battery levels come from two sources (the standard Battery Level characteristic and the proprietary responses), which must be merged into a single stream of changes
+
+use std::time::Duration;
+use futures::stream::StreamExt;
+use ls02::battery::parse_battery_level;
+use ls02::common::*;
+use ls02_sim::{SimConfig, SimWatch};
+use ls02_sim::script::SimEvent;
+
+#[test]
+fn battery_level_values() {
+    assert_eq!(parse_battery_level(&[0]), Some(0));
+    assert_eq!(parse_battery_level(&[100]), Some(100));
+    assert_eq!(parse_battery_level(&[101]), None);
+    assert_eq!(parse_battery_level(&[0xFF]), None);
+    assert_eq!(parse_battery_level(&[]), None);
+    assert_eq!(parse_battery_level(&[50, 0]), None);
+}
+
+#[tokio::test]
+async fn battery_level_read() {
+    let watch = ls02::Watch::new(SimWatch::new(SimConfig {
+        battery: 33,
+        ..Default::default()
+    })).await.unwrap();
+
+    assert_eq!(watch.battery_level().await.unwrap(), 33);
+}
+
+#[tokio::test(start_paused = true)]
+async fn battery_stream_merges_and_dedups() {
+    let sim = SimWatch::default();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+    let battery_stream = watch.battery_stream().await.unwrap();
+
+    // Each simulated change is notified through both sources
+    sim.play(vec![
+        (Duration::from_millis(10), SimEvent::Battery(80)),
+        (Duration::from_millis(10), SimEvent::Battery(80)),
+        (Duration::from_millis(10), SimEvent::Battery(79))
+    ]).await.unwrap();
+
+    // Then each source on its own, with an invalid standard value in between
+    sim.notify(&ls02::CHAR_BATTERY_LEVEL, vec![78]);
+    sim.notify(&ls02::CHAR_BATTERY_LEVEL, vec![150]);
+    sim.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Battery as u8, 78]);
+    sim.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Battery as u8, 77]);
+
+    let levels: Vec<u8> = battery_stream.take(4).collect().await;
+    assert_eq!(levels, [80, 79, 78, 77]);
+}
@@ -0,0 +1,83 @@
+// Dates/times the watch can't represent (or reports with invalid values) must be rejected instead of silently wrapping
+
+use ls02::Timestamp;
+use ls02::common::*;
+use ls02::general::req::SetDateTimeRequest;
+use ls02::general::res::SetDateTimeResponse;
+
+fn date_time_response(date: Date, hour: u8, min: u8, sec: u8) -> SetDateTimeResponse {
+    SetDateTimeResponse {
+        id: CommandId::DateTime,
+        date,
+        hour,
+        min,
+        sec
+    }
+}
+
+#[test]
+fn timestamp_helpers() {
+    let res = date_time_response(Date::new(2024, 5, 6), 12, 34, 56);
+    assert_eq!(res.timestamp(), (Date::new(2024, 5, 6), 12, 34, 56));
+    assert_eq!(res.date(), Date::new(2024, 5, 6));
+    assert_eq!(res.minute_of_day(), 12 * 60 + 34);
+
+    let entry = ls02::history::HeartRateHistoryEntry {
+        date: Date::new(2024, 5, 6),
+        hour: 14,
+        heart_rates: [INVALID_HEART_RATE; 12]
+    };
+    assert_eq!(entry.timestamp(), (Date::new(2024, 5, 6), 14, 0, 0));
+}
+
+#[test]
+fn invalid_dates() {
+    assert!(Date::new(2024, 2, 29).checked().is_ok());
+    assert!(matches!(Date::new(2023, 2, 29).checked(), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(Date::try_from_ymd(-1, 1, 1), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(Date::try_from_ymd(70000, 1, 1), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(SetDateTimeRequest::try_new(Date::new(2024, 5, 6), 24, 0, 0), Err(ls02::Error::InvalidArgument(_))));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_conversions() {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    let naive_date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    assert_eq!(NaiveDate::try_from(Date::new(2024, 2, 29)).unwrap(), naive_date);
+    assert_eq!(Date::try_from(naive_date).unwrap(), Date::new(2024, 2, 29));
+
+    assert!(matches!(NaiveDate::try_from(Date::new(2023, 2, 29)), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(NaiveDate::try_from(Date::new(2024, 13, 1)), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(Date::try_from(NaiveDate::from_ymd_opt(-5, 1, 1).unwrap()), Err(ls02::Error::InvalidArgument(_))));
+
+    let naive_date_time = NaiveDateTime::try_from(date_time_response(Date::new(2024, 5, 6), 12, 34, 56)).unwrap();
+    assert_eq!(naive_date_time, NaiveDate::from_ymd_opt(2024, 5, 6).unwrap().and_hms_opt(12, 34, 56).unwrap());
+    assert!(matches!(NaiveDateTime::try_from(date_time_response(Date::new(2024, 5, 6), 25, 0, 0)), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(NaiveDateTime::try_from(date_time_response(Date::new(2024, 4, 31), 0, 0, 0)), Err(ls02::Error::InvalidArgument(_))));
+
+    // Leap seconds are clamped
+    let leap_second = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap().and_time(NaiveTime::from_hms_milli_opt(23, 59, 59, 1500).unwrap());
+    assert_eq!(SetDateTimeRequest::try_from(leap_second).unwrap(), SetDateTimeRequest::new(Date::new(2016, 12, 31), 23, 59, 59));
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_conversions() {
+    use time::{Month, PrimitiveDateTime, Time};
+
+    let date = time::Date::from_calendar_date(2024, Month::February, 29).unwrap();
+    assert_eq!(time::Date::try_from(Date::new(2024, 2, 29)).unwrap(), date);
+    assert_eq!(Date::try_from(date).unwrap(), Date::new(2024, 2, 29));
+
+    assert!(matches!(time::Date::try_from(Date::new(2023, 2, 29)), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(time::Date::try_from(Date::new(2024, 0, 1)), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(Date::try_from(time::Date::from_calendar_date(-5, Month::January, 1).unwrap()), Err(ls02::Error::InvalidArgument(_))));
+
+    let date_time = PrimitiveDateTime::try_from(date_time_response(Date::new(2024, 5, 6), 12, 34, 56)).unwrap();
+    assert_eq!(date_time, PrimitiveDateTime::new(time::Date::from_calendar_date(2024, Month::May, 6).unwrap(), Time::from_hms(12, 34, 56).unwrap()));
+    assert!(matches!(PrimitiveDateTime::try_from(date_time_response(Date::new(2024, 5, 6), 12, 60, 0)), Err(ls02::Error::InvalidArgument(_))));
+
+    assert_eq!(SetDateTimeRequest::try_from(date_time).unwrap(), SetDateTimeRequest::new(Date::new(2024, 5, 6), 12, 34, 56));
+}
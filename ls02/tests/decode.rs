@@ -0,0 +1,100 @@
+// Responses sharing an ID must be told apart by their characteristic, sub-ID and size
+
+use ls02::{ParseMode, Response, CHAR_DATA2_N, CHAR_GENERAL_N_1, CHAR_GENERAL_RW_1};
+use ls02::common::*;
+
+// 18-byte entry for the given (big-endian) year, whose high byte is what a sub-ID would be
+fn day_hour_entry(year: u16) -> Vec<u8> {
+    let mut raw = vec![CommandId::HeartRate as u8];
+    raw.extend(year.to_be_bytes());
+    raw.extend([6, 15, 10]);
+    raw.extend([60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, INVALID_HEART_RATE]);
+    raw
+}
+
+#[test]
+fn day_hour_entry_date_colliding_with_sub_id() {
+    for sub_id in [HeartRateCommandSubId::DataToday, HeartRateCommandSubId::DataEnd, HeartRateCommandSubId::Enable, HeartRateCommandSubId::DataPeriodic] {
+        let year = ((sub_id as u16) << 8) | 0x10;
+        let raw = day_hour_entry(year);
+        assert_eq!(raw.len(), 18);
+        assert_eq!(raw[1], sub_id as u8);
+
+        match Response::decode_strict(CHAR_DATA2_N.uuid, &raw) {
+            Response::HeartRateDataDayHourEntry(entry) => {
+                assert_eq!(entry.date, Date::new(year, 6, 15));
+                assert_eq!(entry.hour, 10);
+                assert_eq!(entry.heart_rates[0], 60);
+                assert_eq!(entry.heart_rates[11], INVALID_HEART_RATE);
+            },
+            res => panic!("{:?} decoded as {:?}", sub_id, res)
+        }
+        assert!(matches!(Response::decode(CHAR_DATA2_N.uuid, &raw), Response::HeartRateDataDayHourEntry(_)));
+    }
+}
+
+#[test]
+fn sub_id_commands_still_decoded() {
+    let today = [CommandId::HeartRate as u8, HeartRateCommandSubId::DataToday as u8, 0x07, 0xE8, 6, 15, 10, 30, 120, 50, 80];
+    assert!(matches!(Response::decode_strict(CHAR_DATA2_N.uuid, &today), Response::HeartRateDataToday(_)));
+
+    let end = [CommandId::HeartRate as u8, HeartRateCommandSubId::DataEnd as u8, 0];
+    assert!(matches!(Response::decode_strict(CHAR_DATA2_N.uuid, &end), Response::HeartRateDataEnd(_)));
+}
+
+#[test]
+fn heart_rate_menu_split_by_size() {
+    let periodic = [CommandId::HeartRateMenuGeneral as u8, HeartRateMenuCommandSubId::InMenu as u8, 0, 72];
+    match Response::decode_strict(CHAR_GENERAL_N_1.uuid, &periodic) {
+        Response::HeartRateMenuDataPeriodic(res) => assert_eq!(res.heart_rate, 72),
+        res => panic!("Decoded as {:?}", res)
+    }
+
+    let change = [CommandId::HeartRateMenuGeneral as u8, HeartRateMenuCommandSubId::InMenu as u8];
+    assert!(matches!(Response::decode_strict(CHAR_GENERAL_N_1.uuid, &change), Response::HeartRateMenuChange(_)));
+
+    let leaving = [CommandId::HeartRateMenuGeneral as u8, HeartRateMenuCommandSubId::LeavingMenu as u8, 0, 75];
+    assert!(matches!(Response::decode_strict(CHAR_GENERAL_N_1.uuid, &leaving), Response::HeartRateMenuDataLeaving(_)));
+
+    let periodic = [CommandId::HeartRateMenuData as u8, HeartRateMenuCommandSubId::InMenu as u8, 0, 72];
+    match Response::decode_strict(CHAR_DATA2_N.uuid, &periodic) {
+        Response::HeartRateMenuPeriodic(res) => assert_eq!(res.heart_rate, 72),
+        res => panic!("Decoded as {:?}", res)
+    }
+
+    let move_down = [CommandId::HeartRateMenuData as u8, HeartRateMenuCommandSubId::InMenu as u8];
+    assert!(matches!(Response::decode_strict(CHAR_DATA2_N.uuid, &move_down), Response::HeartRateMenuMoveDown(_)));
+
+    // A size in between is only decoded leniently, as the longest one fitting
+    let longer = [CommandId::HeartRateMenuGeneral as u8, HeartRateMenuCommandSubId::InMenu as u8, 0, 72, 0xAB];
+    assert!(Response::decode_strict(CHAR_GENERAL_N_1.uuid, &longer).is_unknown());
+    let (res, trailing) = Response::decode_with(CHAR_GENERAL_N_1.uuid, &longer, ParseMode::Lenient);
+    assert!(matches!(res, Response::HeartRateMenuDataPeriodic(_)));
+    assert_eq!(trailing, [0xAB]);
+}
+
+#[test]
+fn wrong_characteristic_is_unknown() {
+    let battery = [CommandId::Battery as u8, 80];
+    assert!(matches!(Response::decode(CHAR_GENERAL_N_1.uuid, &battery), Response::Battery(_)));
+
+    for char_uuid in [CHAR_DATA2_N.uuid, CHAR_GENERAL_RW_1.uuid] {
+        match Response::decode(char_uuid, &battery) {
+            Response::Unknown { char, bytes } => {
+                assert_eq!(char, char_uuid);
+                assert_eq!(bytes, battery);
+            },
+            res => panic!("Decoded as {:?}", res)
+        }
+    }
+
+    // Same for a data2 response received through the general characteristic
+    let raw = day_hour_entry(2024);
+    assert_eq!(Response::decode(CHAR_GENERAL_N_1.uuid, &raw), Response::Unknown { char: CHAR_GENERAL_N_1.uuid, bytes: raw.clone() });
+}
+
+#[test]
+fn empty_and_unknown_ids_are_unknown() {
+    assert_eq!(Response::decode(CHAR_GENERAL_N_1.uuid, &[]), Response::Unknown { char: CHAR_GENERAL_N_1.uuid, bytes: Vec::new() });
+    assert!(Response::decode(CHAR_GENERAL_N_1.uuid, &[0x02, 0x00]).is_unknown());
+}
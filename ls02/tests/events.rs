@@ -0,0 +1,64 @@
+// The same event must come out no matter which command ID / characteristic the watch used to notify it
+
+use std::time::Duration;
+use futures::stream::StreamExt;
+use ls02::WatchEvent;
+use ls02::common::*;
+use ls02::data2::res::*;
+use ls02_sim::{encode, SimWatch};
+use ls02_sim::script::SimEvent;
+
+#[tokio::test(start_paused = true)]
+async fn general_and_data2_variants_match() {
+    let sim = SimWatch::default();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+    let date = sim.state().date;
+
+    let events = watch.events().await.unwrap();
+
+    // General (or regular Data2) variants, as the simulator sends them
+    sim.play(vec![
+        (Duration::from_millis(10), SimEvent::Pulse(DevicePulseType::Ring)),
+        (Duration::from_millis(10), SimEvent::HeartRateMenuMeasurement(72)),
+        (Duration::from_millis(10), SimEvent::HeartRatePeriodic(65))
+    ]).await.unwrap();
+
+    // Then the *Unk2 (and Data2 menu) variants, with an echo (not an event) in between
+    sim.notify(&ls02::CHAR_DATA2_N, encode(&DevicePulseUnk2Response {
+        id: CommandId::DevicePulseUnk2,
+        pulse_type: DevicePulseType::Ring
+    }));
+    sim.notify(&ls02::CHAR_GENERAL_N_1, vec![CommandId::Weather as u8, WeatherDate::Today as u8]);
+    sim.notify(&ls02::CHAR_DATA2_N, encode(&HeartRateMenuPeriodicResponse {
+        id: CommandId::HeartRateMenuData,
+        sub_id: HeartRateMenuCommandSubId::InMenu,
+        unk_maybe_pad: 0,
+        heart_rate: 72
+    }));
+    sim.notify(&ls02::CHAR_DATA2_N, encode(&HeartRateDataPeriodicUnk2Response {
+        id: CommandId::HeartRateUnk2,
+        sub_id: HeartRateCommandSubId::DataPeriodic,
+        date,
+        hour: 8,
+        min: 0,
+        heart_rate: 65
+    }));
+
+    let expected = [
+        WatchEvent::Pulse(DevicePulseType::Ring),
+        WatchEvent::HeartRateMenuMeasurement(72),
+        WatchEvent::HeartRate { date, hour: 8, min: 0, heart_rate: 65 }
+    ];
+    let received: Vec<WatchEvent> = events.take(6).collect().await;
+    assert_eq!(received[..3], expected);
+    assert_eq!(received[3..], expected);
+}
+
+#[test]
+fn non_events_are_ignored() {
+    let res = ls02::Response::SetWeather(ls02::general::res::SetWeatherResponse {
+        id: CommandId::Weather,
+        weather_date: WeatherDate::Today
+    });
+    assert_eq!(WatchEvent::from_response(&res), None);
+}
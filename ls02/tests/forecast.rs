@@ -0,0 +1,73 @@
+// Forecasts must reach the watch in Celsius, as signed bytes within what it can display
+
+use ls02::CommandRequest;
+use ls02::common::*;
+use ls02::general::forecast::*;
+
+#[test]
+fn temperature_conversion() {
+    assert_eq!(TemperatureUnit::Celsius.to_celsius(-4.6), -5);
+    assert_eq!(TemperatureUnit::Fahrenheit.to_celsius(23.0), -5);
+    assert_eq!(TemperatureUnit::Fahrenheit.to_celsius(98.6), 37);
+    assert_eq!(TemperatureUnit::Fahrenheit.from_celsius(-40), -40.0);
+
+    assert_eq!(TemperatureUnit::Celsius.to_celsius(150.0), 99);
+    assert_eq!(TemperatureUnit::Fahrenheit.to_celsius(-500.0), -99);
+    assert_eq!(clamp_temperature(i8::MIN), -99);
+}
+
+#[test]
+fn wmo_codes() {
+    assert_eq!(WeatherType::from_wmo_code(0, false), Some(WeatherType::Sunny));
+    assert_eq!(WeatherType::from_wmo_code(0, true), Some(WeatherType::Night));
+    assert_eq!(WeatherType::from_wmo_code(2, true), Some(WeatherType::CloudyNight));
+    assert_eq!(WeatherType::from_wmo_code(65, false), Some(WeatherType::VeryRainy));
+    assert_eq!(WeatherType::from_wmo_code(65, true), Some(WeatherType::RainyNight));
+    assert_eq!(WeatherType::from_wmo_code(75, true), Some(WeatherType::Snowy));
+    assert_eq!(WeatherType::from_wmo_code(99, false), Some(WeatherType::Stormy));
+    assert_eq!(WeatherType::from_wmo_code(4, false), None);
+
+    assert_eq!(WeatherType::from_wmo_code(3, false), Some(WeatherType::SunnyCloudy));
+
+    assert!(is_night_hour(23) && is_night_hour(0) && is_night_hour(6));
+    assert!(!is_night_hour(7) && !is_night_hour(19));
+}
+
+#[test]
+fn wmo_code_wire_values() {
+    // Values from the weather type table in RE.md
+    let wire_value = |code, is_night| WeatherType::from_wmo_code(code, is_night).map(u8::from);
+    let groups = [
+        (0, 0x01, 0x0C),
+        (1, 0x02, 0x0D),
+        (3, 0x02, 0x0D),
+        (45, 0x0A, 0x0A),
+        (51, 0x06, 0x0E),
+        (61, 0x06, 0x0E),
+        (62, 0x05, 0x0E),
+        (63, 0x05, 0x0E),
+        (64, 0x07, 0x0E),
+        (65, 0x07, 0x0E),
+        (71, 0x08, 0x08),
+        (80, 0x06, 0x0E),
+        (82, 0x07, 0x0E),
+        (95, 0x04, 0x04)
+    ];
+
+    for (code, day_value, night_value) in groups {
+        assert_eq!(wire_value(code, false), Some(day_value), "WMO code {} by day", code);
+        assert_eq!(wire_value(code, true), Some(night_value), "WMO code {} at night", code);
+    }
+}
+
+#[test]
+fn forecast_requests() {
+    let forecast = Forecast::new(-3, DayWeather::new(WeatherType::Snowy, 1, -7), [
+        DayWeather::new(WeatherType::SunnyCloudy, 4, -2),
+        DayWeather::new(WeatherType::Sunny, 120, 0),
+        DayWeather::from_unit(WeatherType::Rainy, 50.0, 41.0, TemperatureUnit::Fahrenheit)
+    ]);
+
+    assert_eq!(forecast.today_request().build().unwrap(), [0x11, 0x01, 0x08, 0x00, 0xFD, 0x01, 0xF9]);
+    assert_eq!(forecast.following_days_request().build().unwrap(), [0x11, 0x02, 0x02, 0x00, 0x04, 0xFE, 0x01, 0x00, 99, 0x00, 0x05, 0x00, 10, 5]);
+}
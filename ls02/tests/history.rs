@@ -0,0 +1,70 @@
+// History dumps must be collected until the watch's end marker, whichever request variant the watch answers
+
+use std::time::Duration;
+use tokio::time::Instant;
+use ls02_sim::{SimConfig, SimWatch};
+
+#[tokio::test]
+async fn heart_rate_until_end_marker() {
+    let sim = SimWatch::default();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let mut progress = Vec::new();
+    let history = watch.sync_heart_rate(|entry_count| progress.push(entry_count)).await.unwrap();
+
+    let state = sim.state();
+    let expected_entries = ls02_sim::history::heart_rate_entries(&state);
+    assert!(!expected_entries.is_empty());
+    assert_eq!(history.entries.len(), expected_entries.len());
+    for (entry, expected_entry) in history.entries.iter().zip(expected_entries.iter()) {
+        assert_eq!((entry.date, entry.hour, entry.heart_rates), (expected_entry.date, expected_entry.hour, expected_entry.heart_rates));
+    }
+
+    let today = ls02_sim::history::heart_rate_today(&state);
+    assert_eq!(history.today.map(|today| (today.date, today.avg_heart_rate)), Some((today.date, today.avg_heart_rate)));
+
+    assert_eq!(progress, (1..=expected_entries.len()).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn recorded_steps_until_end_marker() {
+    let sim = SimWatch::default();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let mut progress = Vec::new();
+    let history = watch.sync_recorded_steps(|entry_count| progress.push(entry_count)).await.unwrap();
+
+    let expected_entries = ls02_sim::history::steps_entries(&sim.state());
+    assert!(!expected_entries.is_empty());
+    assert_eq!(history.entries, expected_entries);
+    assert_eq!(progress, (1..=expected_entries.len()).collect::<Vec<_>>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn silent_watch_times_out() {
+    let watch = ls02::Watch::new(SimWatch::new(SimConfig {
+        silent: true,
+        ..Default::default()
+    })).await.unwrap().with_timeout(Duration::from_secs(3));
+
+    let mut progress_calls = 0;
+    assert!(matches!(watch.sync_heart_rate(|_| progress_calls += 1).await, Err(ls02::Error::Timeout(_))));
+    assert!(matches!(watch.sync_recorded_steps(|_| progress_calls += 1).await, Err(ls02::Error::Timeout(_))));
+    assert_eq!(progress_calls, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn recorded_steps_general_fallback() {
+    let sim = SimWatch::new(SimConfig {
+        data2_recorded_steps: false,
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let start = Instant::now();
+    let history = watch.sync_recorded_steps(|_| {}).await.unwrap();
+
+    // The Data2 request (0x0A) went unanswered for the whole timeout before falling back to the General one (0xB2)
+    assert!(start.elapsed() >= watch.timeout());
+    assert_eq!(history.entries, ls02_sim::history::steps_entries(&sim.state()));
+}
@@ -0,0 +1,43 @@
+// Newer firmwares may send values/fields we don't know about yet
+
+use ls02::{CommandResponse, ParseMode, Response, CHAR_GENERAL_N_1};
+use ls02::common::*;
+
+#[test]
+fn unknown_enum_values() {
+    let raw = [CommandId::Sport as u8, SportCommandSubId::Start as u8, 0x30, 0x00];
+
+    let res = ls02::general::res::SportStatusResponse::parse_lenient(&raw).unwrap();
+    assert_eq!(res.kind, SportKind::Unknown(0x30));
+    assert_eq!(u8::from(res.kind), 0x30);
+    assert!(ls02::general::res::SportStatusResponse::parse(&raw).is_err());
+
+    assert!(matches!(Response::decode(CHAR_GENERAL_N_1.uuid, &raw), Response::SportStatus(_)));
+    assert!(Response::decode_strict(CHAR_GENERAL_N_1.uuid, &raw).is_unknown());
+}
+
+#[test]
+fn known_enum_values() {
+    for value in 0..=u8::MAX {
+        let alert_type = AlertType::from(value);
+        assert_eq!(u8::from(alert_type), value);
+        assert_eq!(alert_type.is_unknown(), value == 3 || value > 23);
+    }
+    assert_eq!(DevicePulseType::from(10), DevicePulseType::Ring);
+    assert_eq!(SilentModeStatus::from(8), SilentModeStatus::On);
+}
+
+#[test]
+fn trailing_bytes() {
+    let raw = [CommandId::Battery as u8, 80, 0xAB, 0xCD];
+
+    let (res, trailing) = ls02::general::res::BatteryResponse::parse_with(&raw, ParseMode::Lenient).unwrap();
+    assert_eq!(res.battery_percentage, 80);
+    assert_eq!(trailing, [0xAB, 0xCD]);
+    assert!(matches!(ls02::general::res::BatteryResponse::parse(&raw), Err(ls02::Error::TrailingBytes { parsed_len: 2, .. })));
+
+    let (res, trailing) = Response::decode_with(CHAR_GENERAL_N_1.uuid, &raw, ParseMode::Lenient);
+    assert!(matches!(res, Response::Battery(_)));
+    assert_eq!(trailing, [0xAB, 0xCD]);
+    assert!(Response::decode_strict(CHAR_GENERAL_N_1.uuid, &raw).is_unknown());
+}
@@ -0,0 +1,127 @@
+// Pair keys must survive being stored and never use the byte unpaired watches report for unset key bytes, and pairing only succeeds once the watch reports our key
+
+use std::time::Duration;
+use btleplug::api::BDAddr;
+use tokio::time::Instant;
+use ls02::{MemoryKeyStore, PairKeyStore};
+use ls02::pairing::{random_pair_key, PAIRING_TIMEOUT};
+use ls02_sim::{SimConfig, SimWatch};
+
+#[test]
+fn random_keys_are_set() {
+    for _ in 0..1000 {
+        assert!(!random_pair_key().contains(&0xFF));
+    }
+}
+
+#[test]
+fn file_key_store() {
+    let path = std::env::temp_dir().join(format!("ls02-pair-keys-{}", std::process::id())).join("pair_keys");
+    let address_1 = BDAddr::from([0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    let address_2 = BDAddr::from([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+
+    let mut store = ls02::FileKeyStore::new(&path);
+    assert_eq!(store.load(address_1).unwrap(), None);
+
+    store.store(address_1, [1, 2, 3, 4]).unwrap();
+    store.store(address_2, [0x0A, 0xB0, 0x00, 0xFE]).unwrap();
+
+    let mut store = ls02::FileKeyStore::new(&path);
+    assert_eq!(store.load(address_1).unwrap(), Some([1, 2, 3, 4]));
+    assert_eq!(store.load(address_2).unwrap(), Some([0x0A, 0xB0, 0x00, 0xFE]));
+
+    store.remove(address_1).unwrap();
+    assert_eq!(ls02::FileKeyStore::new(&path).load(address_1).unwrap(), None);
+
+    std::fs::write(&path, "not a key\n").unwrap();
+    assert!(matches!(store.load(address_2), Err(ls02::Error::KeyStore(_))));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn verified_pairing() {
+    // The user takes a while to accept the pairing dialog
+    let sim = SimWatch::new(SimConfig {
+        pairing_delay: Duration::from_secs(8),
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    watch.pair_verified([1, 2, 3, 4]).await.unwrap();
+    assert_eq!(sim.state().pair_key, Some([1, 2, 3, 4]));
+    assert_eq!(watch.pair_key().await.unwrap(), [1, 2, 3, 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn pairing_survives_lost_pair_key_response() {
+    // The first current key query gets no answer while the pairing dialog is still shown
+    let sim = SimWatch::new(SimConfig {
+        pairing_delay: Duration::from_secs(8),
+        lost_pair_key_responses: 1,
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let start = Instant::now();
+    watch.pair_verified([1, 2, 3, 4]).await.unwrap();
+    assert!(start.elapsed() < PAIRING_TIMEOUT);
+    assert_eq!(sim.state().lost_pair_key_responses, 0);
+    assert_eq!(sim.state().pair_key, Some([1, 2, 3, 4]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn silent_watch_pairing_times_out() {
+    let sim = SimWatch::new(SimConfig {
+        silent: true,
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let start = Instant::now();
+    match watch.pair_verified([1, 2, 3, 4]).await {
+        Err(ls02::Error::PairKeyMismatch { expected, current }) => assert_eq!((expected, current), ([1, 2, 3, 4], [0xFF; 4])),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+    assert!(start.elapsed() >= PAIRING_TIMEOUT);
+}
+
+#[tokio::test(start_paused = true)]
+async fn rejected_pairing() {
+    let sim = SimWatch::new(SimConfig {
+        pair_key: Some([9, 9, 9, 9]),
+        accept_pairing: false,
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let start = Instant::now();
+    match watch.pair_verified([1, 2, 3, 4]).await {
+        Err(ls02::Error::PairKeyMismatch { expected, current }) => assert_eq!((expected, current), ([1, 2, 3, 4], [9, 9, 9, 9])),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+    assert!(start.elapsed() >= PAIRING_TIMEOUT);
+}
+
+#[tokio::test(start_paused = true)]
+async fn pairing_with_store() {
+    let sim = SimWatch::default();
+    let address = sim.state().address;
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+    let mut store = MemoryKeyStore::new();
+
+    // A new key is generated and stored, then reused
+    let pair_key = watch.pair_with_store(address, &mut store).await.unwrap();
+    assert_eq!(store.load(address).unwrap(), Some(pair_key));
+    assert_eq!(sim.state().pair_key, Some(pair_key));
+    assert_eq!(watch.pair_with_store(address, &mut store).await.unwrap(), pair_key);
+
+    // Keys the watch rejects are never stored
+    let watch = ls02::Watch::new(SimWatch::new(SimConfig {
+        accept_pairing: false,
+        ..Default::default()
+    })).await.unwrap();
+    let mut store = MemoryKeyStore::new();
+    assert!(matches!(watch.pair_with_store(address, &mut store).await, Err(ls02::Error::PairKeyMismatch { .. })));
+    assert_eq!(store.load(address).unwrap(), None);
+}
@@ -0,0 +1,25 @@
+// Raw commands must be written the same way the known commands on each characteristic are
+
+use ls02::raw::{Channel, RawCommand};
+use ls02::registry;
+
+#[test]
+fn raw_write_type_matches_known_requests() {
+    for info in registry::requests() {
+        let channel = Channel::from_write_uuid(info.char.uuid).unwrap_or_else(|| panic!("{} is not on a known channel", info.name));
+        let cmd = RawCommand::on_channel(channel, Vec::new()).unwrap();
+        assert_eq!(Some(cmd.write_type), info.write_type, "{}", info.name);
+    }
+}
+
+#[test]
+fn channels_map_back() {
+    for channel in Channel::ALL {
+        assert_eq!(Channel::from_notify_uuid(channel.notify_char().uuid), Some(channel));
+        if let Some(write_char) = channel.write_char() {
+            assert_eq!(Channel::from_write_uuid(write_char.uuid), Some(channel));
+        }
+    }
+
+    assert!(RawCommand::on_channel(Channel::Battery, vec![0]).is_err());
+}
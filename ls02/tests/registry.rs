@@ -0,0 +1,212 @@
+// The registry must agree with what each request/response type declares
+
+use ls02::{CommandRequest, CommandResponse, Response, CHAR_GENERAL_N_1, CHAR_GENERAL_RW_1, CHAR_DATA2_N, CHAR_DATA2_RW};
+use ls02::registry::{self, Direction};
+use ls02::common::*;
+
+macro_rules! check_request {
+    ($module:ident :: $dir:ident :: $t:ident) => {{
+        let name = concat!(stringify!($module), "::", stringify!($dir), "::", stringify!($t));
+        let info = registry::find(name).unwrap_or_else(|| panic!("{} is not in the registry", name));
+        assert_eq!(info.direction, Direction::Request, "{}", name);
+        assert_eq!(info.char.uuid, <ls02::$module::$dir::$t as CommandRequest>::CHAR.uuid, "{}", name);
+        assert_eq!(info.write_type, Some(<ls02::$module::$dir::$t as CommandRequest>::WRITE_TYPE), "{}", name);
+        name
+    }};
+}
+
+macro_rules! check_response {
+    ($module:ident :: $dir:ident :: $t:ident) => {{
+        let name = concat!(stringify!($module), "::", stringify!($dir), "::", stringify!($t));
+        let info = registry::find(name).unwrap_or_else(|| panic!("{} is not in the registry", name));
+        assert_eq!(info.direction, Direction::Response, "{}", name);
+        assert_eq!(info.char.uuid, <ls02::$module::$dir::$t as CommandResponse>::CHAR.uuid, "{}", name);
+        assert_eq!(info.write_type, None, "{}", name);
+        name
+    }};
+}
+
+#[test]
+fn requests_match_types() {
+    let checked = [
+        check_request!(general::req::PairRequest),
+        check_request!(general::req::PairKeyRequest),
+        check_request!(general::req::BatteryRequest),
+        check_request!(general::req::SetDateTimeRequest),
+        check_request!(general::req::FirmwareRequest),
+        check_request!(general::req::ResetRequest),
+        check_request!(general::req::ConfigDisplayFormatsRequest),
+        check_request!(general::req::AlertStartBatchRequest),
+        check_request!(general::req::AlertNextBatchRequest),
+        check_request!(general::req::AlertPushRequest),
+        check_request!(general::req::SetUserInfoRequest),
+        check_request!(general::req::SetWeatherTodayRequest),
+        check_request!(general::req::SetWeatherFollowingDaysRequest),
+        check_request!(general::req::RecordedStepsRequest),
+        check_request!(data2::req::HeartRateDataRequest),
+        check_request!(data2::req::HeartRateEnableRequest),
+        check_request!(data2::req::HeartRateDisableRequest),
+        check_request!(data2::req::RecordedStepsRequest)
+    ];
+
+    // Every request in the registry has a type (and it's checked above)
+    for info in registry::requests() {
+        assert!(checked.contains(&info.name), "{} is not checked", info.name);
+    }
+}
+
+#[test]
+fn responses_match_types() {
+    let checked = [
+        check_response!(general::res::PairKeyResponse),
+        check_response!(general::res::BatteryResponse),
+        check_response!(general::res::SetDateTimeResponse),
+        check_response!(general::res::FirmwareResponse),
+        check_response!(general::res::ConfigDisplayFormatsResponse),
+        check_response!(general::res::DevicePulseResponse),
+        check_response!(general::res::SetWeatherResponse),
+        check_response!(general::res::HeartRateMenuDataPeriodicResponse),
+        check_response!(general::res::HeartRateMenuDataLeavingResponse),
+        check_response!(general::res::HeartRateMenuChangeResponse),
+        check_response!(general::res::SilentModeChangeResponse),
+        check_response!(general::res::SportStatusResponse),
+        check_response!(general::res::RecordedStepsEntryResponse),
+        check_response!(general::res::RecordedStepsEndResponse),
+        check_response!(data2::res::HeartRateDataTodayResponse),
+        check_response!(data2::res::HeartRateDataEndResponse),
+        check_response!(data2::res::HeartRateDataDayHourEntryResponse),
+        check_response!(data2::res::HeartRateDataPeriodicResponse),
+        check_response!(data2::res::HeartRateEnableResponse),
+        check_response!(data2::res::HeartRateDisableResponse),
+        check_response!(data2::res::StepsResponse),
+        check_response!(data2::res::HeartRateMenuPeriodicResponse),
+        check_response!(data2::res::HeartRateMenuMoveDownResponse),
+        check_response!(data2::res::HeartRateDataTodayUnk2Response),
+        check_response!(data2::res::StepsUnk2Response),
+        check_response!(data2::res::HeartRateDataPeriodicUnk2Response),
+        check_response!(data2::res::DevicePulseUnk2Response),
+        check_response!(data2::res::RecordedStepsEntryResponse),
+        check_response!(data2::res::RecordedStepsEndResponse)
+    ];
+
+    for info in registry::responses().filter(|info| info.name.contains("::")) {
+        assert!(checked.contains(&info.name), "{} is not checked", info.name);
+    }
+}
+
+// Payload bytes are 1, which is valid for most fields (enums start at 1, dates have no day/month 0)
+fn sample_frame(info: &registry::CommandInfo) -> Vec<u8> {
+    let mut raw = vec![1; info.len.unwrap()];
+    raw[0] = info.id as u8;
+    if let Some(sub_id) = info.sub_id {
+        raw[1] = sub_id;
+    }
+    raw
+}
+
+// A frame the registry identifies as this type, which must also be decoded as it
+macro_rules! check_decode {
+    ($module:ident :: $dir:ident :: $t:ident => $variant:ident) => {
+        check_decode!($module::$dir::$t => $variant, sample_frame)
+    };
+    ($module:ident :: $dir:ident :: $t:ident => $variant:ident, $frame:expr) => {{
+        let name = concat!(stringify!($module), "::", stringify!($dir), "::", stringify!($t));
+        let info = registry::find(name).unwrap_or_else(|| panic!("{} is not in the registry", name));
+        let raw: Vec<u8> = $frame(info);
+        assert_eq!(registry::identify(info.char.uuid, &raw).map(|info| info.name), Some(name));
+        let res = Response::decode(info.char.uuid, &raw);
+        assert!(matches!(res, Response::$variant(_)), "{} decoded as {:?}", name, res);
+        name
+    }};
+}
+
+#[test]
+fn responses_decode_as_identified() {
+    let checked = [
+        check_decode!(general::res::PairKeyResponse => PairKey),
+        check_decode!(general::res::BatteryResponse => Battery),
+        check_decode!(general::res::SetDateTimeResponse => SetDateTime),
+        check_decode!(general::res::FirmwareResponse => Firmware),
+        check_decode!(general::res::ConfigDisplayFormatsResponse => ConfigDisplayFormats),
+        check_decode!(general::res::DevicePulseResponse => DevicePulse),
+        check_decode!(general::res::SetWeatherResponse => SetWeather),
+        check_decode!(general::res::HeartRateMenuDataPeriodicResponse => HeartRateMenuDataPeriodic),
+        check_decode!(general::res::HeartRateMenuDataLeavingResponse => HeartRateMenuDataLeaving),
+        check_decode!(general::res::HeartRateMenuChangeResponse => HeartRateMenuChange),
+        check_decode!(general::res::SilentModeChangeResponse => SilentModeChange),
+        check_decode!(general::res::SportStatusResponse => SportStatus, |_| vec![CommandId::Sport as u8, SportCommandSubId::Start as u8, 1, 1]),
+        check_decode!(general::res::RecordedStepsEntryResponse => RecordedStepsEntryGeneral),
+        check_decode!(general::res::RecordedStepsEndResponse => RecordedStepsEndGeneral),
+        check_decode!(data2::res::HeartRateDataTodayResponse => HeartRateDataToday),
+        check_decode!(data2::res::HeartRateDataEndResponse => HeartRateDataEnd),
+        check_decode!(data2::res::HeartRateDataDayHourEntryResponse => HeartRateDataDayHourEntry),
+        check_decode!(data2::res::HeartRateDataPeriodicResponse => HeartRateDataPeriodic),
+        check_decode!(data2::res::HeartRateEnableResponse => HeartRateEnable),
+        check_decode!(data2::res::HeartRateDisableResponse => HeartRateDisable),
+        check_decode!(data2::res::StepsResponse => Steps),
+        check_decode!(data2::res::HeartRateMenuPeriodicResponse => HeartRateMenuPeriodic),
+        check_decode!(data2::res::HeartRateMenuMoveDownResponse => HeartRateMenuMoveDown),
+        check_decode!(data2::res::HeartRateDataTodayUnk2Response => HeartRateDataTodayUnk2),
+        check_decode!(data2::res::StepsUnk2Response => StepsUnk2),
+        check_decode!(data2::res::HeartRateDataPeriodicUnk2Response => HeartRateDataPeriodicUnk2),
+        check_decode!(data2::res::DevicePulseUnk2Response => DevicePulseUnk2),
+        check_decode!(data2::res::RecordedStepsEntryResponse => RecordedStepsEntryData2),
+        check_decode!(data2::res::RecordedStepsEndResponse => RecordedStepsEndData2)
+    ];
+
+    for info in registry::responses().filter(|info| info.name.contains("::")) {
+        assert!(checked.contains(&info.name), "{} is not checked", info.name);
+    }
+
+    // Typeless ones are identified, but not decoded
+    for info in registry::responses().filter(|info| !info.name.contains("::")) {
+        let raw = sample_frame(info);
+        assert_eq!(registry::identify(info.char.uuid, &raw).map(|info| info.name), Some(info.name));
+        assert!(Response::decode(info.char.uuid, &raw).is_unknown(), "{}", info.name);
+    }
+}
+
+#[test]
+fn names_are_unique() {
+    for (i, info) in registry::COMMANDS.iter().enumerate() {
+        assert!(registry::COMMANDS[i + 1..].iter().all(|other| other.name != info.name), "{} is listed twice", info.name);
+    }
+}
+
+#[test]
+fn fixed_size_requests_match_built_size() {
+    let built = [
+        ("general::req::PairRequest", ls02::general::req::PairRequest::new([1, 2, 3, 4]).build().unwrap()),
+        ("general::req::PairKeyRequest", ls02::general::req::PairKeyRequest::new().build().unwrap()),
+        ("general::req::BatteryRequest", ls02::general::req::BatteryRequest::new().build().unwrap()),
+        ("general::req::SetDateTimeRequest", ls02::general::req::SetDateTimeRequest::new(Date::new(2024, 1, 1), 0, 0, 0).build().unwrap()),
+        ("general::req::FirmwareRequest", ls02::general::req::FirmwareRequest::new().build().unwrap()),
+        ("general::req::ConfigDisplayFormatsRequest", ls02::general::req::ConfigDisplayFormatsRequest::new(DistanceUnit::Metric, TimeFormat::H24).build().unwrap()),
+        ("general::req::AlertPushRequest", ls02::general::req::AlertPushRequest::new().build().unwrap()),
+        ("general::req::SetUserInfoRequest", ls02::general::req::SetUserInfoRequest::new(180, 75, 5, 10000, UserLiftWristMode::On, 30, UserGender::Male).build().unwrap()),
+        ("general::req::SetWeatherTodayRequest", ls02::general::req::SetWeatherTodayRequest::new(WeatherType::Sunny, 20, 25, 15).build().unwrap()),
+        ("general::req::RecordedStepsRequest", ls02::general::req::RecordedStepsRequest::new().build().unwrap()),
+        ("data2::req::HeartRateDataRequest", ls02::data2::req::HeartRateDataRequest::new().build().unwrap()),
+        ("data2::req::HeartRateEnableRequest", ls02::data2::req::HeartRateEnableRequest::new().build().unwrap()),
+        ("data2::req::HeartRateDisableRequest", ls02::data2::req::HeartRateDisableRequest::new().build().unwrap()),
+        ("data2::req::RecordedStepsRequest", ls02::data2::req::RecordedStepsRequest::new().build().unwrap())
+    ];
+
+    for (name, raw) in built {
+        let info = registry::find(name).unwrap();
+        assert_eq!(info.len, Some(raw.len()), "{}", name);
+        assert!(info.matches(info.char.uuid, &raw), "{}", name);
+        assert_eq!(registry::identify(info.char.uuid, &raw).map(|info| info.name), Some(name));
+    }
+}
+
+#[test]
+fn known_ids() {
+    assert!(registry::is_known_id(CommandId::Battery as u8));
+    assert!(registry::is_known_id(CommandId::HeartRateUnk2 as u8));
+    assert!(!registry::is_known_id(CommandId::Invalid as u8));
+    assert!(!registry::is_known_id(0x02));
+
+    let chars = [CHAR_GENERAL_RW_1.uuid, CHAR_GENERAL_N_1.uuid, CHAR_DATA2_RW.uuid, CHAR_DATA2_N.uuid];
+    assert!(registry::COMMANDS.iter().all(|info| chars.contains(&info.char.uuid)));
+}
@@ -0,0 +1,68 @@
+// Resets must only happen with the right confirmation, and rebooting watches must be reconnected, re-paired and restored
+
+use std::time::Duration;
+use ls02::{HostProfile, ResetConfirmation};
+use ls02::common::*;
+use ls02::general::user_info::UserInfo;
+use ls02_sim::{SimConfig, SimWatch};
+
+const PAIR_KEY: PairKey = [0x12, 0x34, 0x56, 0x78];
+
+fn paired_sim(pairing_delay: Duration) -> SimWatch {
+    SimWatch::new(SimConfig {
+        pair_key: Some(PAIR_KEY),
+        pairing_delay,
+        ..Default::default()
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn reboot_reconnects_and_restores() {
+    // Accepting the pairing dialog takes longer than the watch's response timeout
+    let sim = paired_sim(Duration::from_secs(10));
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let user_info = UserInfo::new(180, 75, 40, UserGender::Female).build().unwrap();
+    let profile = HostProfile::new(0, DistanceUnit::Imperial, TimeFormat::H12).with_user_info(user_info.clone());
+    watch.reset(ResetCommandSubId::ResetAndReboot, ResetConfirmation::reboot(PAIR_KEY).with_profile(profile)).await.unwrap();
+
+    let state = sim.state();
+    assert!(state.connected);
+    assert!(!state.pairing_pending);
+    assert_eq!(state.pair_key, Some(PAIR_KEY));
+    assert_eq!((state.distance_unit, state.time_fmt), (DistanceUnit::Imperial, TimeFormat::H12));
+    assert_eq!(state.user_info, Some(user_info));
+
+    // Notifications work again after reconnecting
+    assert_eq!(watch.battery().await.unwrap(), 87);
+}
+
+#[tokio::test(start_paused = true)]
+async fn power_off_stays_disconnected() {
+    let sim = paired_sim(Duration::ZERO);
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    watch.reset(ResetCommandSubId::ResetAndPowerOff, ResetConfirmation::power_off(PAIR_KEY)).await.unwrap();
+
+    let state = sim.state();
+    assert!(!state.connected && !state.powered_on);
+    assert_eq!(state.pair_key, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wrong_confirmation_is_rejected() {
+    let sim = paired_sim(Duration::ZERO);
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let wrong_pair_key = [0; 4];
+    match watch.reset(ResetCommandSubId::ResetAndReboot, ResetConfirmation::reboot(wrong_pair_key)).await {
+        Err(ls02::Error::PairKeyMismatch { expected, current }) => assert_eq!((expected, current), (wrong_pair_key, PAIR_KEY)),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+    assert!(matches!(watch.reset(ResetCommandSubId::ResetAndReboot, ResetConfirmation::power_off(PAIR_KEY)).await, Err(ls02::Error::InvalidArgument(_))));
+
+    // Nothing was reset
+    let state = sim.state();
+    assert!(state.connected);
+    assert_eq!(state.pair_key, Some(PAIR_KEY));
+}
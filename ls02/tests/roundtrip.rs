@@ -0,0 +1,139 @@
+// Byte layouts as documented in RE.md, checking that multi-byte fields end up big-endian on the wire and native in the structs
+
+use binrw::{BinRead, io::Cursor};
+use ls02::{CommandRequest, CommandResponse, MultiFrameRequest};
+use ls02::common::*;
+
+fn parse_request<C: BinRead>(raw: &[u8]) -> C where for<'a> <C as BinRead>::Args<'a>: Default {
+    C::read_le(&mut Cursor::new(raw)).unwrap()
+}
+
+#[test]
+fn date_time_request() {
+    let req = ls02::general::req::SetDateTimeRequest::new(Date::new(2024, 5, 6), 12, 34, 56);
+    let raw = [0x04, 0x07, 0xE8, 0x05, 0x06, 0x0C, 0x22, 0x38];
+
+    assert_eq!(req.build().unwrap(), raw);
+    assert_eq!(parse_request::<ls02::general::req::SetDateTimeRequest>(&raw), req);
+}
+
+#[test]
+fn date_time_response() {
+    let raw = [0x04, 0x07, 0xE4, 0x01, 0x01, 0x08, 0x00, 0x00];
+    let res = ls02::general::res::SetDateTimeResponse::parse(&raw).unwrap();
+
+    assert_eq!(res.date.year, 2020);
+    assert_eq!(res.date, Date::new(2020, 1, 1));
+    assert_eq!((res.hour, res.min, res.sec), (8, 0, 0));
+}
+
+#[test]
+fn date_time_from_system_time() {
+    // 2024/05/06 12:34:56 UTC
+    let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1714998896);
+
+    let req = ls02::general::req::SetDateTimeRequest::from_system_time(time, 0).unwrap();
+    assert_eq!(req, ls02::general::req::SetDateTimeRequest::new(Date::new(2024, 5, 6), 12, 34, 56));
+
+    let req = ls02::general::req::SetDateTimeRequest::from_system_time(time, 12 * 60 * 60).unwrap();
+    assert_eq!(req, ls02::general::req::SetDateTimeRequest::new(Date::new(2024, 5, 7), 0, 34, 56));
+
+    assert_eq!(Date::try_from_unix_days(0).unwrap(), Date::new(1970, 1, 1));
+    assert_eq!(Date::try_from_unix_days(11016).unwrap(), Date::new(2000, 2, 29));
+}
+
+#[test]
+fn reset_request() {
+    let req = ls02::general::req::ResetRequest::new(ResetCommandSubId::ResetAndPowerOff);
+    let raw = [0x07, 0x01];
+
+    assert_eq!(req.build().unwrap(), raw);
+    assert_eq!(parse_request::<ls02::general::req::ResetRequest>(&raw), req);
+}
+
+#[test]
+fn user_info_request() {
+    let req = ls02::general::req::SetUserInfoRequest::new(180, 75, 5, 10000, UserLiftWristMode::On, 30, UserGender::Male);
+    let raw = req.build().unwrap();
+
+    assert_eq!(raw.len(), 0x13);
+    assert_eq!(raw[0x00], CommandId::UserInfo as u8);
+    assert_eq!(raw[0x02], 180);
+    assert_eq!(raw[0x04], 75);
+    assert_eq!(raw[0x05], 5);
+    assert_eq!(&raw[0x08..0x0A], &[0x27, 0x10]);
+    assert_eq!(raw[0x0A], 0x01);
+    assert_eq!(raw[0x0B], 0xA0);
+    assert_eq!(raw[0x0D], 30);
+    assert_eq!(raw[0x0E], 0x01);
+    assert_eq!(&raw[0x0F..], &[0x00, 0x01, 0x01, 0x28]);
+
+    let parsed = parse_request::<ls02::general::req::SetUserInfoRequest>(&raw);
+    assert_eq!(parsed.step_goal, 10000);
+    assert_eq!(parsed, req);
+}
+
+#[test]
+fn recorded_steps_entry() {
+    let raw = [
+        0xB2,
+        0x07, 0xE7, 0x0C, 0x1F, 0x0E,
+        0x04, 0xD2,
+        0x00, 0x28, 0x00,
+        0x01, 0x2C,
+        0x00, 0x32, 0x00,
+        0x03, 0xA6
+    ];
+    let res = ls02::general::res::RecordedStepsEntryResponse::parse(&raw).unwrap();
+
+    assert_eq!(res.entry.date, Date::new(2023, 12, 31));
+    assert_eq!(res.entry.hour, 14);
+    assert_eq!(res.entry.new_all_step_count, 1234);
+    assert_eq!(res.entry.last_new_run_step_min, 40);
+    assert_eq!(res.entry.new_run_step_count, 300);
+    assert_eq!(res.entry.last_new_walk_step_min, 50);
+    assert_eq!(res.entry.new_walk_step_count, 934);
+
+    let mut data2_raw = raw;
+    data2_raw[0] = CommandId::RecordedStepsData2 as u8;
+    let data2_res = ls02::data2::res::RecordedStepsEntryResponse::parse(&data2_raw).unwrap();
+    assert_eq!(data2_res.entry, res.entry);
+}
+
+#[test]
+fn heart_rate_periodic_response() {
+    let raw = [0x18, 0x03, 0x07, 0xE8, 0x02, 0x1D, 0x0A, 0x14, 0x48];
+    let res = ls02::data2::res::HeartRateDataPeriodicResponse::parse(&raw).unwrap();
+
+    assert_eq!(res.date, Date::new(2024, 2, 29));
+    assert_eq!((res.hour, res.min, res.heart_rate), (10, 20, 72));
+}
+
+#[test]
+fn alert_text_utf16be() {
+    let req = ls02::general::req::AlertRequest::new(AlertType::Message, "Hé");
+    assert_eq!(req.text_utf16, vec![0x0048, 0x00E9]);
+
+    let frames = req.fragment(ls02::MAX_WRITE_SIZE).unwrap();
+    assert_eq!(frames[0], [0x0F, 0x00, u8::from(AlertType::Message), 0x04, 0x00, 0x48, 0x00, 0xE9]);
+
+    let start_req = parse_request::<ls02::general::req::AlertStartBatchRequest>(&frames[0]);
+    assert_eq!(start_req.msg_start_text_utf16, req.text_utf16);
+}
+
+#[test]
+fn alert_fragments_at_larger_mtu() {
+    let text = "a".repeat(100);
+    let req = ls02::general::req::AlertRequest::new(AlertType::Message, &text);
+
+    // Start frames have a 4-byte header, next frames a 2-byte one (then the final push frame)
+    let frame_lens = |max_frame_size: usize| req.fragment(max_frame_size).unwrap().iter().map(Vec::len).collect::<Vec<_>>();
+    assert_eq!(frame_lens(ls02::MAX_WRITE_SIZE), [48, 48, 48, 48, 20, 2]);
+    assert_eq!(frame_lens(185 - ls02::transport::ATT_HEADER_SIZE), [182, 24, 2]);
+    assert_eq!(frame_lens(512), [204, 2]);
+
+    let frames = req.fragment(185 - ls02::transport::ATT_HEADER_SIZE).unwrap();
+    let mut text_utf16 = parse_request::<ls02::general::req::AlertStartBatchRequest>(&frames[0]).msg_start_text_utf16;
+    text_utf16.extend(parse_request::<ls02::general::req::AlertNextBatchRequest>(&frames[1]).msg_next_text_utf16);
+    assert_eq!(String::from_utf16(&text_utf16).unwrap(), text);
+}
@@ -0,0 +1,64 @@
+// Multi-byte fields are serialized as native numbers (not as their big-endian wire bytes)
+
+#![cfg(feature = "serde")]
+
+use btleplug::api::ValueNotification;
+use ls02::{CommandRequest, Response, CHAR_DATA2_N};
+use ls02::common::*;
+
+#[test]
+fn date_is_native() {
+    let date = Date::new(2024, 5, 6);
+    let json = serde_json::to_value(date).unwrap();
+
+    assert_eq!(json, serde_json::json!({ "year": 2024, "month": 5, "day": 6 }));
+    assert_eq!(serde_json::from_value::<Date>(json).unwrap(), date);
+}
+
+#[test]
+fn steps_entry_is_native() {
+    let raw = [
+        CommandId::StepsUnk2 as u8,
+        0x07, 0xE8, 0x05, 0x06, 0x0E,
+        0x12, 0x34, 0x00, 0x1E, 0x00,
+        0x01, 0x00, 0x00, 0x2D, 0x00,
+        0x11, 0x34
+    ];
+    let entry = match Response::decode_strict(CHAR_DATA2_N.uuid, &raw) {
+        Response::StepsUnk2(res) => res.entry,
+        res => panic!("Decoded as {:?}", res)
+    };
+    let json = serde_json::to_value(entry).unwrap();
+
+    assert_eq!(json["date"]["year"], 2024);
+    assert_eq!(json["new_all_step_count"], 0x1234);
+    assert_eq!(json["new_run_step_count"], 0x0100);
+    assert_eq!(json["new_walk_step_count"], 0x1134);
+    assert_eq!(serde_json::from_value::<StepsEntry>(json).unwrap(), entry);
+}
+
+#[test]
+fn user_info_request_is_native() {
+    let req = ls02::general::req::SetUserInfoRequest::new(180, 75, 5, 10000, UserLiftWristMode::On, 30, UserGender::Male);
+    let json = serde_json::to_value(&req).unwrap();
+
+    assert_eq!(json["step_goal"], 10000);
+    assert_eq!(json["height_cm"], 180);
+
+    let deserialized = serde_json::from_value::<ls02::general::req::SetUserInfoRequest>(json).unwrap();
+    assert_eq!(deserialized.build().unwrap(), req.build().unwrap());
+}
+
+#[test]
+fn decoded_response_round_trip() {
+    let raw = [CommandId::HeartRate as u8, HeartRateCommandSubId::DataPeriodic as u8, 0x07, 0xE8, 0x05, 0x06, 0x0C, 0x22, 72];
+    let res = Response::from_notification(&ValueNotification { uuid: CHAR_DATA2_N.uuid, value: raw.to_vec() });
+    assert!(matches!(res, Response::HeartRateDataPeriodic(_)));
+
+    let json = serde_json::to_string(&res).unwrap();
+    assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), res);
+
+    let unknown = Response::decode(CHAR_DATA2_N.uuid, &[0x02, 0x00]);
+    let json = serde_json::to_string(&unknown).unwrap();
+    assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), unknown);
+}
@@ -0,0 +1,85 @@
+// Everything above the transport only relies on what WatchTransport promises, which the simulator must honour like a real peripheral does
+
+use btleplug::api::WriteType;
+use futures::stream::StreamExt;
+use ls02::{CommandRequest, CommandResponse, WatchTransport};
+use ls02::common::*;
+use ls02_sim::{SimConfig, SimWatch};
+
+#[tokio::test]
+async fn write_then_notify() {
+    let sim = SimWatch::default();
+    sim.subscribe(&ls02::CHAR_GENERAL_N_1).await.unwrap();
+
+    let mut notif_stream = sim.notifications().await.unwrap();
+    sim.write(&ls02::CHAR_GENERAL_RW_1, &ls02::general::req::BatteryRequest::new().build().unwrap(), WriteType::WithoutResponse).await.unwrap();
+
+    let value_notif = notif_stream.next().await.unwrap();
+    assert_eq!(value_notif.uuid, ls02::CHAR_GENERAL_N_1.uuid);
+    assert_eq!(ls02::general::res::BatteryResponse::parse(&value_notif.value).unwrap().battery_percentage, 87);
+}
+
+#[tokio::test]
+async fn unsubscribed_characteristics_are_not_notified() {
+    let sim = SimWatch::default();
+    sim.subscribe(&ls02::CHAR_DATA2_N).await.unwrap();
+
+    let mut notif_stream = sim.notifications().await.unwrap();
+    sim.write(&ls02::CHAR_GENERAL_RW_1, &ls02::general::req::BatteryRequest::new().build().unwrap(), WriteType::WithoutResponse).await.unwrap();
+    sim.notify(&ls02::CHAR_DATA2_N, vec![0x2A]);
+
+    assert_eq!(notif_stream.next().await.unwrap().value, [0x2A]);
+}
+
+#[tokio::test]
+async fn every_stream_gets_every_notification() {
+    let sim = SimWatch::default();
+    sim.subscribe(&ls02::CHAR_GENERAL_N_1).await.unwrap();
+
+    let mut notif_stream_1 = sim.notifications().await.unwrap();
+    let mut notif_stream_2 = sim.notifications().await.unwrap();
+    sim.notify(&ls02::CHAR_GENERAL_N_1, vec![1, 2, 3]);
+
+    assert_eq!(notif_stream_1.next().await.unwrap().value, [1, 2, 3]);
+    assert_eq!(notif_stream_2.next().await.unwrap().value, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn battery_level_read() {
+    let sim = SimWatch::new(SimConfig {
+        battery: 42,
+        ..Default::default()
+    });
+
+    assert_eq!(sim.read(&ls02::CHAR_BATTERY_LEVEL).await.unwrap(), [42]);
+    assert!(sim.read(&ls02::CHAR_GENERAL_N_1).await.is_err());
+}
+
+#[tokio::test]
+async fn writes_are_limited_by_the_mtu() {
+    let sim = SimWatch::default();
+    assert_eq!(ls02::max_write_size(&sim).await, ls02::MAX_WRITE_SIZE);
+    assert!(sim.write(&ls02::CHAR_GENERAL_RW_1, &[0; ls02::MAX_WRITE_SIZE + 1], WriteType::WithResponse).await.is_err());
+    ls02::write(&sim, ls02::general::req::PairRequest::new([0; 4])).await.unwrap();
+
+    let sim = SimWatch::new(SimConfig {
+        mtu: Some(185),
+        ..Default::default()
+    });
+    assert_eq!(ls02::max_write_size(&sim).await, 185 - ls02::transport::ATT_HEADER_SIZE);
+    sim.write(&ls02::CHAR_GENERAL_RW_1, &[0; 100], WriteType::WithResponse).await.unwrap();
+}
+
+#[tokio::test]
+async fn disconnected_transport_fails() {
+    let sim = SimWatch::default();
+    ls02::write(&sim, ls02::general::req::ResetRequest::new(ResetCommandSubId::ResetAndPowerOff)).await.unwrap();
+
+    assert!(!sim.connected().await.unwrap());
+    assert!(sim.write(&ls02::CHAR_GENERAL_RW_1, &[0x03], WriteType::WithResponse).await.is_err());
+    assert!(sim.read(&ls02::CHAR_BATTERY_LEVEL).await.is_err());
+    assert!(sim.subscribe(&ls02::CHAR_GENERAL_N_1).await.is_err());
+
+    // Powered off, so it can't come back
+    assert!(sim.reconnect().await.is_err());
+}
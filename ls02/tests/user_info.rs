@@ -0,0 +1,87 @@
+// User info is validated before being sent, and unknown bytes keep the official app's values unless overridden
+
+use ls02::CommandRequest;
+use ls02::common::*;
+use ls02::general::req::SetUserInfoRequest;
+use ls02::general::user_info::UserInfo;
+
+// Wire offset of unk0-unk9 in SetUserInfoRequest
+const UNK_OFFSETS: [usize; 10] = [0x01, 0x03, 0x06, 0x07, 0x0B, 0x0C, 0x0F, 0x10, 0x11, 0x12];
+
+fn user_info() -> UserInfo {
+    UserInfo::new(180, 75, 30, UserGender::Male)
+}
+
+fn assert_invalid(user_info: UserInfo) {
+    assert!(matches!(user_info.validate(), Err(ls02::Error::InvalidArgument(_))), "{:?}", user_info);
+    assert!(matches!(user_info.build(), Err(ls02::Error::InvalidArgument(_))), "{:?}", user_info);
+}
+
+#[test]
+fn out_of_range_fields_rejected() {
+    assert_invalid(UserInfo { height_cm: UserInfo::HEIGHT_CM_RANGE.start() - 1, ..user_info() });
+    assert_invalid(UserInfo { height_cm: UserInfo::HEIGHT_CM_RANGE.end() + 1, ..user_info() });
+    assert_invalid(UserInfo { weight_kg: UserInfo::WEIGHT_KG_RANGE.start() - 1, ..user_info() });
+    assert_invalid(UserInfo { weight_kg: UserInfo::WEIGHT_KG_RANGE.end() + 1, ..user_info() });
+    assert_invalid(user_info().with_screen_show_timeout_seconds(UserInfo::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE.start() - 1));
+    assert_invalid(user_info().with_screen_show_timeout_seconds(UserInfo::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE.end() + 1));
+    assert_invalid(user_info().with_step_goal(UserInfo::STEP_GOAL_RANGE.start() - 1));
+    assert_invalid(user_info().with_step_goal(UserInfo::STEP_GOAL_RANGE.end() + 1));
+    assert_invalid(UserInfo { age: UserInfo::AGE_RANGE.start() - 1, ..user_info() });
+    assert_invalid(UserInfo { age: UserInfo::AGE_RANGE.end() + 1, ..user_info() });
+}
+
+#[test]
+fn range_bounds_accepted() {
+    let min = UserInfo::new(*UserInfo::HEIGHT_CM_RANGE.start(), *UserInfo::WEIGHT_KG_RANGE.start(), *UserInfo::AGE_RANGE.start(), UserGender::Female)
+        .with_screen_show_timeout_seconds(*UserInfo::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE.start())
+        .with_step_goal(*UserInfo::STEP_GOAL_RANGE.start());
+    let max = UserInfo::new(*UserInfo::HEIGHT_CM_RANGE.end(), *UserInfo::WEIGHT_KG_RANGE.end(), *UserInfo::AGE_RANGE.end(), UserGender::Male)
+        .with_screen_show_timeout_seconds(*UserInfo::SCREEN_SHOW_TIMEOUT_SECONDS_RANGE.end())
+        .with_step_goal(*UserInfo::STEP_GOAL_RANGE.end());
+
+    for user_info in [min, max] {
+        let req = user_info.build().unwrap();
+        assert_eq!(req.height_cm, user_info.height_cm);
+        assert_eq!(req.weight_kg, user_info.weight_kg);
+        assert_eq!(req.screen_show_timeout_seconds, user_info.screen_show_timeout_seconds);
+        assert_eq!(req.step_goal, user_info.step_goal);
+        assert_eq!(req.age, user_info.age);
+        assert_eq!(req.gender, user_info.gender);
+    }
+}
+
+#[test]
+fn default_unks() {
+    let raw = user_info().build().unwrap().build().unwrap();
+    for (offset, value) in UNK_OFFSETS.iter().zip(SetUserInfoRequest::DEFAULT_UNKS) {
+        assert_eq!(raw[*offset], value, "offset {:#04X}", offset);
+    }
+}
+
+#[test]
+fn raw_unk_overrides() {
+    let req = user_info().with_raw_unk(2, 0xAA).unwrap().with_raw_unk(9, 0x30).unwrap().build().unwrap();
+    let raw = req.build().unwrap();
+
+    for (idx, offset) in UNK_OFFSETS.iter().enumerate() {
+        let expected = match idx {
+            2 => 0xAA,
+            9 => 0x30,
+            _ => SetUserInfoRequest::DEFAULT_UNKS[idx]
+        };
+        assert_eq!(raw[*offset], expected, "unk{}", idx);
+    }
+
+    // Known fields are left untouched
+    assert_eq!(raw[0x02], 180);
+    assert_eq!(raw[0x04], 75);
+    assert_eq!(raw[0x0D], 30);
+}
+
+#[test]
+fn out_of_range_raw_unk_rejected() {
+    assert!(user_info().with_raw_unk(9, 0).is_ok());
+    assert!(matches!(user_info().with_raw_unk(10, 0), Err(ls02::Error::InvalidArgument(_))));
+    assert!(matches!(user_info().with_raw_unk(usize::MAX, 0), Err(ls02::Error::InvalidArgument(_))));
+}
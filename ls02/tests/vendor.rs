@@ -0,0 +1,18 @@
+// Vendor characteristic values are decoded by their looks
+
+use ls02::vendor::VendorValue;
+
+#[test]
+fn decode_text() {
+    assert_eq!(VendorValue::decode(b"RH208DV000924\0\0"), VendorValue::Text("RH208DV000924".to_string()));
+    assert_eq!(VendorValue::decode(b"V1.2 beta"), VendorValue::Text("V1.2 beta".to_string()));
+}
+
+#[test]
+fn decode_binary() {
+    assert_eq!(VendorValue::decode(&[0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC]), VendorValue::Address([0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC]));
+    assert_eq!(VendorValue::decode(&[0x01, 0x02]), VendorValue::Number(0x0201));
+    assert_eq!(VendorValue::decode(&[0x41]), VendorValue::Number(0x41));
+    assert_eq!(VendorValue::decode(&[0x00, 0x00, 0x00]), VendorValue::Bytes(vec![0, 0, 0]));
+    assert_eq!(VendorValue::decode(&[]), VendorValue::Bytes(Vec::new()));
+}
@@ -0,0 +1,144 @@
+// Requests must wait for their own response (skipping anything else the watch notifies meanwhile) and check what the watch echoes back
+
+use std::time::Duration;
+use ls02::common::*;
+use ls02::general::res::*;
+use ls02_sim::{encode, SimConfig, SimWatch};
+use ls02_sim::script::SimEvent;
+
+fn silent_sim() -> SimWatch {
+    SimWatch::new(SimConfig {
+        silent: true,
+        ..Default::default()
+    })
+}
+
+// Notified once the request is already waiting for its response
+fn respond_later(sim: &SimWatch, value: Vec<u8>) {
+    let sim = sim.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sim.notify(&ls02::CHAR_GENERAL_N_1, value);
+    });
+}
+
+#[tokio::test]
+async fn battery_and_firmware() {
+    let watch = ls02::Watch::new(SimWatch::new(SimConfig {
+        battery: 64,
+        firmware: *b"RH208DV001000",
+        ..Default::default()
+    })).await.unwrap();
+
+    assert_eq!(watch.battery().await.unwrap(), 64);
+    assert_eq!(watch.firmware().await.unwrap(), "RH208DV001000");
+}
+
+#[tokio::test]
+async fn settings_are_echoed() {
+    let sim = SimWatch::default();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    watch.set_datetime(Date::new(2024, 5, 6), 12, 34, 56).await.unwrap();
+    watch.set_display_formats(DistanceUnit::Imperial, TimeFormat::H12).await.unwrap();
+    watch.set_weather_today(WeatherType::Sunny, 20, 25, 15).await.unwrap();
+
+    let state = sim.state();
+    assert_eq!((state.date, state.hour, state.min, state.sec), (Date::new(2024, 5, 6), 12, 34, 56));
+    assert_eq!((state.distance_unit, state.time_fmt), (DistanceUnit::Imperial, TimeFormat::H12));
+    assert!(state.weather_today.is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn silent_watch_times_out() {
+    let watch = ls02::Watch::new(silent_sim()).await.unwrap().with_timeout(Duration::from_secs(2));
+
+    assert!(matches!(watch.battery().await, Err(ls02::Error::Timeout(timeout)) if timeout == Duration::from_secs(2)));
+    assert!(matches!(watch.set_display_formats(DistanceUnit::Metric, TimeFormat::H24).await, Err(ls02::Error::Timeout(_))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn unrelated_notifications_are_skipped() {
+    let sim = silent_sim();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    sim.play(vec![
+        (Duration::from_millis(10), SimEvent::Steps(12)),
+        (Duration::from_millis(10), SimEvent::Pulse(DevicePulseType::MusicPauseResume)),
+        (Duration::from_millis(10), SimEvent::HeartRatePeriodic(70))
+    ]);
+    respond_later(&sim, encode(&BatteryResponse {
+        id: CommandId::Battery,
+        battery_percentage: 55
+    }));
+
+    assert_eq!(watch.battery().await.unwrap(), 55);
+}
+
+#[tokio::test(start_paused = true)]
+async fn mismatched_display_formats_echo() {
+    let sim = silent_sim();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    let echo = ConfigDisplayFormatsResponse {
+        id: CommandId::Config,
+        distance_unit: DistanceUnit::Metric,
+        time_fmt: TimeFormat::H24
+    };
+    respond_later(&sim, encode(&echo));
+
+    match watch.set_display_formats(DistanceUnit::Imperial, TimeFormat::H24).await {
+        Err(ls02::Error::UnexpectedResponse(ls02::Response::ConfigDisplayFormats(res))) => assert_eq!(res, echo),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn mismatched_datetime_echo() {
+    let sim = silent_sim();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    respond_later(&sim, encode(&SetDateTimeResponse {
+        id: CommandId::DateTime,
+        date: Date::new(2020, 1, 1),
+        hour: 8,
+        min: 0,
+        sec: 0
+    }));
+
+    assert!(matches!(watch.set_datetime(Date::new(2024, 5, 6), 12, 34, 56).await, Err(ls02::Error::UnexpectedResponse(ls02::Response::SetDateTime(_)))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn mismatched_weather_echo() {
+    let sim = silent_sim();
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+
+    respond_later(&sim, encode(&SetWeatherResponse {
+        id: CommandId::Weather,
+        weather_date: WeatherDate::FollowingDays
+    }));
+
+    assert!(matches!(watch.set_weather_today(WeatherType::Sunny, 20, 25, 15).await, Err(ls02::Error::UnexpectedResponse(ls02::Response::SetWeather(_)))));
+}
+
+#[tokio::test]
+async fn mtu_override() {
+    let text = "a".repeat(100);
+
+    let sim = SimWatch::new(SimConfig {
+        mtu: Some(185),
+        ..Default::default()
+    });
+    let watch = ls02::Watch::new(sim.clone()).await.unwrap();
+    assert_eq!(watch.max_write_size().await, 182);
+
+    let watch = watch.with_mtu(23);
+    assert_eq!(watch.max_write_size().await, 20);
+    watch.send_alert(AlertType::Message, text.clone()).await.unwrap();
+    assert_eq!(sim.state().last_alert, Some((AlertType::Message, text.clone())));
+
+    // Overriding with more than what was actually negotiated makes the writes fail
+    let watch = ls02::Watch::new(SimWatch::default()).await.unwrap().with_mtu(185);
+    assert!(matches!(watch.send_alert(AlertType::Message, text).await, Err(ls02::Error::Transport(_))));
+}
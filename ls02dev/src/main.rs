@@ -1,16 +1,24 @@
 use std::error::Error;
 use std::time::Duration;
-use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter, WriteType, Characteristic};
-use btleplug::platform::{Manager, Peripheral};
+use btleplug::api::{BDAddr, Peripheral as _};
 use futures::stream::StreamExt;
 use tokio::time;
-use ls02;
-
-const WATCH_PAIR_KEY: ls02::common::PairKey = [1, 2, 3, 4];
+use ls02::WatchTransport;
+use ls02::general::alert::{AlertMessage, TruncationPolicy};
+use ls02::general::user_info::UserInfo;
+use ls02::general::forecast::{DayWeather, Forecast, TemperatureUnit};
+use ls02_sim::{SimWatch, script::SimEvent};
+
+// Keys are kept next to the executable, one per watch
+fn pair_key_store() -> std::io::Result<ls02::FileKeyStore> {
+    let exe_path = std::env::current_exe()?;
+    Ok(ls02::FileKeyStore::new(exe_path.with_file_name("pair_keys")))
+}
 
-async fn send_thread(watch: Peripheral) -> Result<(), Box<dyn Error>> {
+// The watch is already paired with this key, thus pairing again just gets the already-paired response
+async fn send_thread(watch: impl WatchTransport, pair_key: ls02::common::PairKey) -> ls02::Result<()> {
     println!("[ls02dev.Send] Starting programmed commands...");
-    ls02::write::<ls02::general::req::PairRequest>(&watch, ls02::general::req::PairRequest::new(WATCH_PAIR_KEY)).await?;
+    ls02::write::<ls02::general::req::PairRequest>(&watch, ls02::general::req::PairRequest::new(pair_key)).await?;
 
     time::sleep(Duration::from_secs(1)).await;
 
@@ -23,138 +31,194 @@ async fn send_thread(watch: Peripheral) -> Result<(), Box<dyn Error>> {
     // time::sleep(Duration::from_secs(3)).await;
 
     // ls02::write::<ls02::data2::req::RecordedStepsRequest>(&watch, ls02::data2::req::RecordedStepsRequest::new()).await?;
-    // watch.write(&ls02::CHAR_GENERAL_RW_1, &[37, 171, 2], WriteType::WithoutResponse).await?;
+    // ls02::raw::write(&watch, &ls02::raw::RawCommand::new(ls02::CHAR_GENERAL_RW_1, vec![37, 171, 2]).with_write_type(WriteType::WithoutResponse)).await?;
 
     ls02::write(&watch, ls02::data2::req::RecordedStepsRequest::new()).await?;
 
     Ok(())
 }
 
-async fn notification_receive_thread(watch: Peripheral, notif_char: Characteristic, name: &str) -> Result<(), Box<dyn Error>> {
-    watch.subscribe(&notif_char).await?;
-
-    let mut notif_stream = watch.notifications().await?;
-    loop {
-        if let Some(data) = notif_stream.next().await {
-            let mut unk_res = true;
-
-            macro_rules! do_response {
-                ($t:ty) => {
-                    match ls02::read::<$t>(&data, &notif_char).await {
-                        ls02::CommandResponseResult::Ok(res) => {
-                            println!("[ls02dev.Notify.{}] {:?}", name, res);
-                            unk_res = false;
-                        },
-                        ls02::CommandResponseResult::ImproperCharacteristic => unk_res = false,
-                        _ => {}
-                    };
-                };
-            }
-
-            do_response!(ls02::general::res::PairKeyResponse);
-            do_response!(ls02::general::res::BatteryResponse);
-            do_response!(ls02::general::res::SetDateTimeResponse);
-            do_response!(ls02::general::res::FirmwareResponse);
-            do_response!(ls02::general::res::DevicePulseResponse);
-            do_response!(ls02::general::res::SetWeatherResponse);
-            do_response!(ls02::general::res::HeartRateMenuDataPeriodicResponse);
-            do_response!(ls02::general::res::HeartRateMenuDataLeavingResponse);
-            do_response!(ls02::general::res::HeartRateMenuChangeResponse);
-            do_response!(ls02::general::res::SilentModeChangeResponse);
-            do_response!(ls02::general::res::SportStatusResponse);
-            do_response!(ls02::general::res::RecordedStepsEntryResponse);
-            do_response!(ls02::general::res::RecordedStepsEndResponse);
-
-            do_response!(ls02::data2::res::HeartRateDataTodayResponse);
-            do_response!(ls02::data2::res::HeartRateDataDayHourEntryResponse);
-            do_response!(ls02::data2::res::HeartRateDataEndResponse);
-            do_response!(ls02::data2::res::HeartRateDataPeriodicResponse);
-            do_response!(ls02::data2::res::HeartRateEnableResponse);
-            do_response!(ls02::data2::res::HeartRateDisableResponse);
-            do_response!(ls02::data2::res::StepsResponse);
-            do_response!(ls02::data2::res::HeartRateMenuPeriodicResponse);
-            do_response!(ls02::data2::res::HeartRateMenuMoveDownResponse);
-            do_response!(ls02::data2::res::HeartRateDataTodayUnk2Response);
-            do_response!(ls02::data2::res::StepsUnk2Response);
-            do_response!(ls02::data2::res::HeartRateDataPeriodicUnk2Response);
-            do_response!(ls02::data2::res::DevicePulseUnk2Response);
-            do_response!(ls02::data2::res::RecordedStepsEntryResponse);
-            do_response!(ls02::data2::res::RecordedStepsEndResponse);
-
-            if unk_res {
-                println!("[ls02dev.Notify.{}] Unknown response: {:?}", name, &data.value);
-            }
+// Every channel is listened to, including the ones no known command uses yet
+async fn notification_receive_thread(watch: impl WatchTransport) -> ls02::Result<()> {
+    let mut frames = ls02::raw::frames(&watch).await?;
+    while let Some(frame) = frames.next().await {
+        let name = format!("{:?}", frame.channel);
+        match ls02::Response::decode_with(frame.channel.notify_char().uuid, &frame.bytes, parse_mode()) {
+            (ls02::Response::Unknown { bytes, .. }, _) => println!("[ls02dev.Notify.{}] Unknown response: {:?}", name, bytes),
+            (res, trailing) if !trailing.is_empty() => println!("[ls02dev.Notify.{}] {:?} (trailing bytes: {:?})", name, res, trailing),
+            (res, _) => println!("[ls02dev.Notify.{}] {:?}", name, res)
         }
     }
+
+    Ok(())
+}
+
+// Read-only, thus safe to run against any watch
+async fn vendor_probe(watch: &impl WatchTransport) {
+    println!("[ls02dev.Vendor] Probing vendor service...");
+    for field in ls02::vendor::probe(watch).await.fields {
+        match field.value() {
+            Some(value) => println!("[ls02dev.Vendor] {}: {}", field.char_uuid, value),
+            None => println!("[ls02dev.Vendor] {}: <unreadable>", field.char_uuid)
+        }
+    }
+}
+
+async fn sim_main() -> Result<(), Box<dyn Error>> {
+    println!("[ls02dev.Main] Using simulated watch...");
+    let sim = SimWatch::default();
+
+    if vendor_mode() {
+        vendor_probe(&sim).await;
+        return Ok(());
+    }
+
+    let sim_c = sim.clone();
+    tokio::spawn(async move {
+        if let Err(err) = notification_receive_thread(sim_c).await {
+            eprintln!("[ls02dev.Notify] ERROR: {}", err);
+        }
+    });
+
+    // Give the receive thread some time to subscribe
+    time::sleep(Duration::from_millis(100)).await;
+
+    // Simulated watches are new every time, thus their keys aren't kept
+    let watch = ls02::Watch::new(sim.clone()).await?;
+    let pair_key = watch.pair_with_store(sim.state().address, &mut ls02::MemoryKeyStore::new()).await?;
+    println!("[ls02dev.Main] Paired with key {:?}", pair_key);
+    send_thread(sim.clone(), pair_key).await?;
+
+    // Same commands, through the higher-level client (which also checks echoed values)
+    println!("[ls02dev.Main] Battery: {}%, firmware: {}", watch.battery().await?, watch.firmware().await?);
+    println!("[ls02dev.Main] Battery (standard service): {}%", watch.battery_level().await?);
+    watch.set_datetime(ls02::common::Date::new(2024, 5, 6), 12, 34, 56).await?;
+    watch.set_display_formats(ls02::common::DistanceUnit::Metric, ls02::common::TimeFormat::H24).await?;
+    let forecast = Forecast::new(-3, DayWeather::new(ls02::common::WeatherType::Snowy, 1, -7), [
+        DayWeather::new(ls02::common::WeatherType::SunnyCloudy, 4, -2),
+        DayWeather::new(ls02::common::WeatherType::Sunny, 9, 0),
+        DayWeather::from_unit(ls02::common::WeatherType::Rainy, 50.0, 41.0, TemperatureUnit::Fahrenheit)
+    ]);
+    watch.set_forecast(&forecast).await?;
+    println!("[ls02dev.Main] Date/time, display formats and weather forecast confirmed by the watch");
+
+    let user_info = UserInfo::new(175, 70, 30, ls02::common::UserGender::Male)
+        .with_step_goal(10000)
+        .with_raw_unk(9, 0x30)?;
+    watch.set_user_info(user_info.build()?).await?;
+    println!("[ls02dev.Main] User info received by the watch: {:?}", sim.state().user_info);
+
+    let alert_msg = AlertMessage::new(ls02::common::AlertType::Message, &"Hello from ls02dev! \u{1F600} This message is long enough to need several frames, and even to be truncated. ".repeat(2))
+        .with_title("ls02dev")
+        .with_truncation(TruncationPolicy::Ellipsis);
+    println!("[ls02dev.Main] Alert frames: {:?}", alert_msg.preview(ls02::max_write_size(&sim).await)?);
+    watch.send_alert_message(&alert_msg).await?;
+    println!("[ls02dev.Main] Alert shown by the watch: {:?}", sim.state().last_alert);
+
+    let hr_history = watch.sync_heart_rate(|_| {}).await?;
+    let steps_history = watch.sync_recorded_steps(|_| {}).await?;
+    println!("[ls02dev.Main] Synced history: {} heart rate entries (today: {:?}), {} recorded step entries", hr_history.entries.len(), hr_history.today, steps_history.entries.len());
+
+    let mut events = watch.events().await?;
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            println!("[ls02dev.Event] {:?}", event);
+        }
+    });
+
+    sim.play(vec![
+        (Duration::from_millis(500), SimEvent::Pulse(ls02::common::DevicePulseType::Ring)),
+        (Duration::from_millis(500), SimEvent::SilentMode(ls02::common::SilentModeStatus::On)),
+        (Duration::from_millis(500), SimEvent::SportStart(ls02::common::SportKind::Jogging)),
+        (Duration::from_millis(500), SimEvent::SportFinish(ls02::common::SportKind::Jogging)),
+        (Duration::from_millis(500), SimEvent::HeartRateMenuEnter),
+        (Duration::from_millis(500), SimEvent::HeartRateMenuMeasurement(72)),
+        (Duration::from_millis(500), SimEvent::HeartRateMenuLeave(74))
+    ]).await?;
+
+    time::sleep(Duration::from_millis(500)).await;
+
+    // A confirmation for another watch (pair key) is rejected before anything is sent
+    if let Err(err) = watch.reset(ls02::common::ResetCommandSubId::ResetAndReboot, ls02::ResetConfirmation::reboot([9, 9, 9, 9])).await {
+        println!("[ls02dev.Main] Reset refused: {}", err);
+    }
+
+    let profile = ls02::HostProfile::new(0, ls02::common::DistanceUnit::Imperial, ls02::common::TimeFormat::H12).with_user_info(user_info.build()?);
+    watch.reset(ls02::common::ResetCommandSubId::ResetAndReboot, ls02::ResetConfirmation::reboot(pair_key).with_profile(profile)).await?;
+    let state = sim.state();
+    println!("[ls02dev.Main] Watch reset, re-paired and restored: pair key {:?}, {:?} {:02}:{:02}:{:02}, {:?}/{:?}, user info {}", state.pair_key, state.date, state.hour, state.min, state.sec, state.distance_unit, state.time_fmt, state.user_info.is_some());
+    Ok(())
+}
+
+const SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// With --strict, anything not exactly matching the known responses is shown as unknown
+fn parse_mode() -> ls02::ParseMode {
+    if std::env::args().any(|arg| arg == "--strict") {
+        ls02::ParseMode::Strict
+    }
+    else {
+        ls02::ParseMode::Lenient
+    }
+}
+
+// With the "vendor" subcommand, only the vendor service is probed
+fn vendor_mode() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "vendor")
+}
+
+// Value following a "--name" argument, if present
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let manager = Manager::new().await?;
-    let adapter_list = manager.adapters().await?;
-    if adapter_list.is_empty() {
-        eprintln!("[ls02dev.Main] ERROR: No Bluetooth adapters found...");
+    if std::env::args().any(|arg| arg == "--sim") {
+        return sim_main().await;
     }
 
-    for adapter in adapter_list.iter() {
-        println!("Starting scan...");
-        let scan_filter = ScanFilter {
-            services: vec![ls02::SERVICE_GENERAL, ls02::SERVICE_DATA2]
-        };
-        adapter.start_scan(scan_filter).await.expect("[ls02dev.Main] ERROR: Can't scan BLE adapter for connected devices...");
-        time::sleep(Duration::from_secs(2)).await;
+    let adapter = ls02::discover::select_adapter(arg_value("--adapter").as_deref()).await?;
+    let address = arg_value("--address").map(|address| address.parse::<BDAddr>()).transpose()?;
+
+    println!("[ls02dev.Main] Starting scan...");
+    let candidate = ls02::discover::find(&adapter, address, SCAN_TIMEOUT).await?;
+    println!("[ls02dev.Main] Found {}! (already connected: {}) --- MAC: {}, RSSI: {:?}", candidate.name.as_deref().unwrap_or(ls02::DEVICE_NAME), candidate.is_connected, candidate.address, candidate.rssi);
 
-        let peripherals = adapter.peripherals().await?;
+    println!("[ls02dev.Main] Connecting and discovering services...");
+    let watch = candidate.connect().await?;
+    let peripheral = watch.transport().clone();
+
+    if vendor_mode() {
+        vendor_probe(&peripheral).await;
+        peripheral.disconnect().await?;
+        return Ok(());
+    }
 
-        if peripherals.is_empty() {
-            eprintln!("[ls02dev.Main] ERROR: BLE peripheral devices were not found...");
+    let peripheral_c = peripheral.clone();
+    tokio::spawn(async move {
+        if let Err(err) = notification_receive_thread(peripheral_c).await {
+            eprintln!("[ls02dev.Notify] ERROR: {}", err);
         }
-        else {
-            for peripheral in &peripherals {
-                let properties = peripheral.properties().await?;
-                let is_connected = peripheral.is_connected().await?;
-
-                // TODO: we already check for LS02's services on the filter, do we really need to also check the device name?
-                match &properties.as_ref().unwrap().local_name {
-                    Some(name) => {
-                        if name == ls02::DEVICE_NAME {
-                            println!("[ls02dev.Main] Found {}! (already connected: {}) --- MAC: {}", ls02::DEVICE_NAME, is_connected, properties.as_ref().unwrap().address);
-        
-                            let is_connected = peripheral.is_connected().await?;
-                            if !is_connected {
-                                peripheral.connect().await?;
-                            }
-        
-                            println!("[ls02dev.Main] Discovering services...");
-                            peripheral.discover_services().await?;
-        
-                            let peripheral_c = peripheral.clone();
-                            tokio::spawn(async move {
-                                notification_receive_thread(peripheral_c, ls02::CHAR_GENERAL_N_1, "General").await.unwrap()
-                            });
-        
-                            let peripheral_c = peripheral.clone();
-                            tokio::spawn(async move {
-                                notification_receive_thread(peripheral_c, ls02::CHAR_DATA2_N, "Data2").await.unwrap()
-                            });
-        
-                            let peripheral_c = peripheral.clone();
-                            tokio::spawn(async move {
-                                send_thread(peripheral_c).await.unwrap()
-                            });
-        
-                            while peripheral.is_connected().await? {}
-        
-                            println!("[ls02dev.Main] Watch disconnected...");
-                            peripheral.disconnect().await?;
-                            break;
-                        }
-                    },
-                    _ => {}
-                }
-            }
+    });
+
+    println!("[ls02dev.Main] Pairing (accept the dialog on the watch if shown)...");
+    let pair_key = watch.pair_with_store(candidate.address, &mut pair_key_store()?).await?;
+    println!("[ls02dev.Main] Paired with key {:?}", pair_key);
+
+    let peripheral_c = peripheral.clone();
+    tokio::spawn(async move {
+        if let Err(err) = send_thread(peripheral_c, pair_key).await {
+            eprintln!("[ls02dev.Send] ERROR: {}", err);
         }
+    });
+
+    while peripheral.is_connected().await? {
+        time::sleep(Duration::from_millis(500)).await;
     }
 
+    println!("[ls02dev.Main] Watch disconnected...");
+    peripheral.disconnect().await?;
+
     Ok(())
 }
\ No newline at end of file